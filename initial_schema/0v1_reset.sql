@@ -1,13 +1,35 @@
+DROP TABLE IF EXISTS item_option CASCADE;
+DROP TABLE IF EXISTS item_component CASCADE;
 DROP TABLE IF EXISTS item CASCADE;
+DROP TABLE IF EXISTS favorite_restaurant CASCADE;
+DROP TABLE IF EXISTS favorite_food CASCADE;
 DROP TABLE IF EXISTS comments CASCADE;
+DROP TABLE IF EXISTS food_review CASCADE;
+DROP TABLE IF EXISTS food_price_history CASCADE;
+DROP TABLE IF EXISTS promotion CASCADE;
+DROP TABLE IF EXISTS availability_window CASCADE;
+DROP TABLE IF EXISTS food_stock CASCADE;
+DROP TABLE IF EXISTS bundle_slot_choice CASCADE;
+DROP TABLE IF EXISTS bundle_slot CASCADE;
+DROP TABLE IF EXISTS bundle_item CASCADE;
+DROP TABLE IF EXISTS bundle CASCADE;
+DROP TABLE IF EXISTS food_option CASCADE;
+DROP TABLE IF EXISTS food_option_group CASCADE;
 DROP TABLE IF EXISTS food CASCADE;
+DROP TABLE IF EXISTS menu_section CASCADE;
 DROP TABLE IF EXISTS comment_votes CASCADE;
 DROP TABLE IF EXISTS owner CASCADE;
 DROP TABLE IF EXISTS orders_active CASCADE;
 DROP TABLE IF EXISTS orders_archived CASCADE;
+DROP TABLE IF EXISTS order_status_history CASCADE;
 DROP TABLE IF EXISTS orders CASCADE;
 DROP TABLE IF EXISTS payment CASCADE;
+DROP TABLE IF EXISTS restaurant_hours_exception CASCADE;
+DROP TABLE IF EXISTS delivery_zone CASCADE;
 DROP TABLE IF EXISTS restaurant_hours CASCADE;
+DROP TABLE IF EXISTS restaurant_image CASCADE;
+DROP TABLE IF EXISTS restaurant_tag CASCADE;
+DROP TABLE IF EXISTS tag CASCADE;
 DROP TABLE IF EXISTS restaurant CASCADE;
 DROP TABLE IF EXISTS account CASCADE;
 DROP TYPE IF EXISTS weekday CASCADE;
@@ -46,6 +46,46 @@ CREATE TABLE IF NOT EXISTS restaurant (
   location DOUBLE PRECISION[2],
   created_on TIMESTAMP NOT NULL DEFAULT NOW()
 );
+-- bumped on every update, sent as the ETag and checked against If-Match
+ALTER TABLE restaurant ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+
+-- managed tags, restaurant.tags and food.tag are kept as their slugs for older clients
+CREATE TABLE IF NOT EXISTS tag (
+  id SERIAL PRIMARY KEY,
+  slug TEXT NOT NULL UNIQUE,
+  label_fa TEXT NOT NULL,
+  label_en TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS restaurant_tag (
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  tag_id INT NOT NULL REFERENCES tag(id) ON DELETE CASCADE,
+  PRIMARY KEY (restaurant_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS idx_restaurant_tag_tag ON restaurant_tag(tag_id);
+
+-- restaurant.image mirrors the url of the cover image
+CREATE TABLE IF NOT EXISTS restaurant_image (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  url TEXT NOT NULL,
+  caption TEXT,
+  sort_order INT NOT NULL DEFAULT 0,
+  is_cover BOOLEAN NOT NULL DEFAULT FALSE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_restaurant_image_restaurant ON restaurant_image(restaurant_id, sort_order);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_restaurant_image_cover ON restaurant_image(restaurant_id) WHERE is_cover;
+
+-- restaurants from before the gallery keep their image, as the cover of a one image gallery
+DO $$
+BEGIN
+  INSERT INTO restaurant_image (restaurant_id, url, is_cover)
+  SELECT restaurant.id, restaurant.image, TRUE
+  FROM restaurant
+  WHERE restaurant.image <> ''
+  AND NOT EXISTS (SELECT 1 FROM restaurant_image WHERE restaurant_image.restaurant_id = restaurant.id);
+END$$;
 
 CREATE TABLE IF NOT EXISTS food (
   id SERIAL PRIMARY KEY,
@@ -54,12 +94,192 @@ CREATE TABLE IF NOT EXISTS food (
   image TEXT NOT NULL,
   tag TEXT NOT NULL,
   price INT NOT NULL,
-  discount BOOLEAN NOT NULL,
-  discount_price INT,
   ingredient TEXT[] NOT NULL,
-  available BOOLEAN NOT NULL,
-  CONSTRAINT discount_price_required 
-    CHECK (NOT discount OR (discount AND discount_price IS NOT NULL))
+  available BOOLEAN NOT NULL
+);
+ALTER TABLE food ADD COLUMN IF NOT EXISTS tag_id INT REFERENCES tag(id) ON DELETE SET NULL;
+ALTER TABLE food ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+
+-- headings of the menu, an optional time of day window can pass midnight
+CREATE TABLE IF NOT EXISTS menu_section (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  sort_order INT NOT NULL DEFAULT 0,
+  available_from TIME,
+  available_to TIME,
+  CONSTRAINT section_window_complete
+    CHECK ((available_from IS NULL) = (available_to IS NULL))
+);
+CREATE INDEX IF NOT EXISTS idx_menu_section_restaurant ON menu_section(restaurant_id, sort_order);
+
+-- the section has to belong to the food's restaurant, checked by the server
+ALTER TABLE food ADD COLUMN IF NOT EXISTS section_id INT REFERENCES menu_section(id) ON DELETE SET NULL;
+ALTER TABLE food ADD COLUMN IF NOT EXISTS sort_order INT NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS idx_food_section ON food(restaurant_id, section_id, sort_order);
+
+-- structured labels, the values are the lowercase names of `types::dietary::{Allergen, Diet}`
+-- a vegan food also carries `vegetarian`
+-- `allergens` NULL means not declared, which is not the same as '{}' (none)
+ALTER TABLE food ADD COLUMN IF NOT EXISTS allergens TEXT[];
+ALTER TABLE food ADD COLUMN IF NOT EXISTS diets TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE food ADD COLUMN IF NOT EXISTS spicy_level INT NOT NULL DEFAULT 0
+  CONSTRAINT food_spicy_level CHECK (spicy_level BETWEEN 0 AND 3);
+
+-- the column used to default to '{}', those foods were never declared
+DO $$
+BEGIN
+  IF EXISTS (
+    SELECT 1 FROM information_schema.columns
+    WHERE table_name = 'food' AND column_name = 'allergens' AND is_nullable = 'NO'
+  ) THEN
+    ALTER TABLE food ALTER COLUMN allergens DROP NOT NULL, ALTER COLUMN allergens DROP DEFAULT;
+    UPDATE food SET allergens = NULL WHERE allergens = '{}';
+  END IF;
+END$$;
+
+-- discounts that run by themselves, see `types::promotion`
+-- no target means the whole restaurant, `days` NULL means every day
+CREATE TABLE IF NOT EXISTS promotion (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('percent', 'fixed')),
+  value INT NOT NULL,
+  food_id INT REFERENCES food(id) ON DELETE CASCADE,
+  tag_id INT REFERENCES tag(id) ON DELETE CASCADE,
+  section_id INT REFERENCES menu_section(id) ON DELETE CASCADE,
+  starts_at TIMESTAMP,
+  ends_at TIMESTAMP,
+  days weekday[],
+  window_from TIME,
+  window_to TIME,
+  active BOOLEAN NOT NULL DEFAULT TRUE,
+  CONSTRAINT promotion_value
+    CHECK ((kind = 'percent' AND value BETWEEN 1 AND 100) OR (kind = 'fixed' AND value > 0)),
+  CONSTRAINT promotion_one_target CHECK (num_nonnulls(food_id, tag_id, section_id) <= 1),
+  CONSTRAINT promotion_window CHECK ((window_from IS NULL) = (window_to IS NULL))
+);
+CREATE INDEX IF NOT EXISTS idx_promotion_restaurant ON promotion(restaurant_id);
+
+-- foods used to carry their own `discount` flag and `discount_price`
+-- a running discount becomes a promotion of that food without an end, then the columns go
+DO $$
+BEGIN
+  IF EXISTS (
+    SELECT 1 FROM information_schema.columns
+    WHERE table_name = 'food' AND column_name = 'discount'
+  ) THEN
+    INSERT INTO promotion (restaurant_id, name, kind, value, food_id)
+    SELECT restaurant_id, 'Discount', 'fixed', price - discount_price, id
+    FROM food
+    WHERE discount AND discount_price IS NOT NULL AND discount_price < price;
+    ALTER TABLE food DROP CONSTRAINT IF EXISTS discount_price_required;
+    ALTER TABLE food DROP COLUMN discount, DROP COLUMN discount_price;
+    ALTER TABLE IF EXISTS food_price_history DROP COLUMN IF EXISTS discount, DROP COLUMN IF EXISTS discount_price;
+  END IF;
+END$$;
+
+-- weekly windows a food or a whole menu section can be ordered in, like restaurant_hours
+-- no rows means any time, a section with rows ignores its `available_from`/`available_to`
+CREATE TABLE IF NOT EXISTS availability_window (
+  id SERIAL PRIMARY KEY,
+  food_id INT REFERENCES food(id) ON DELETE CASCADE,
+  section_id INT REFERENCES menu_section(id) ON DELETE CASCADE,
+  day_of_week weekday NOT NULL,
+  open_time TIME NOT NULL,
+  close_time TIME NOT NULL,
+  CONSTRAINT window_one_target CHECK ((food_id IS NULL) <> (section_id IS NULL)),
+  CONSTRAINT window_not_empty CHECK (open_time <> close_time)
+);
+CREATE INDEX IF NOT EXISTS idx_availability_window_food ON availability_window(food_id);
+CREATE INDEX IF NOT EXISTS idx_availability_window_section ON availability_window(section_id);
+
+-- optional daily stock of a food, checkout takes from `remaining`
+-- `remaining` goes back to `daily_quantity` every day at `reset_time`
+-- `sold_out` means running out made the food unavailable, the reset makes it available again
+CREATE TABLE IF NOT EXISTS food_stock (
+  food_id INT PRIMARY KEY REFERENCES food(id) ON DELETE CASCADE,
+  daily_quantity INT NOT NULL CHECK (daily_quantity >= 0),
+  remaining INT NOT NULL CHECK (remaining >= 0),
+  reset_time TIME NOT NULL DEFAULT '00:00',
+  last_reset TIMESTAMP NOT NULL DEFAULT LOCALTIMESTAMP,
+  sold_out BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- sizes and add-ons, a customer picks min_select..max_select options of every group
+CREATE TABLE IF NOT EXISTS food_option_group (
+  id SERIAL PRIMARY KEY,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  min_select INT NOT NULL DEFAULT 0,
+  max_select INT NOT NULL DEFAULT 1,
+  sort_order INT NOT NULL DEFAULT 0,
+  CONSTRAINT option_group_limits
+    CHECK (min_select >= 0 AND max_select >= 1 AND min_select <= max_select)
+);
+CREATE INDEX IF NOT EXISTS idx_food_option_group_food ON food_option_group(food_id, sort_order);
+
+CREATE TABLE IF NOT EXISTS food_option (
+  id SERIAL PRIMARY KEY,
+  group_id INT NOT NULL REFERENCES food_option_group(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  price_delta INT NOT NULL DEFAULT 0,
+  available BOOLEAN NOT NULL DEFAULT TRUE,
+  sort_order INT NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_food_option_group ON food_option(group_id, sort_order);
+
+-- combos sold at their own price, made of fixed items and slots the customer fills with one of the choices
+CREATE TABLE IF NOT EXISTS bundle (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  section_id INT REFERENCES menu_section(id) ON DELETE SET NULL,
+  name TEXT NOT NULL,
+  description TEXT NOT NULL DEFAULT '',
+  image TEXT NOT NULL DEFAULT '',
+  price INT NOT NULL CHECK (price >= 0),
+  available BOOLEAN NOT NULL DEFAULT TRUE,
+  sort_order INT NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_bundle_restaurant ON bundle(restaurant_id, sort_order);
+
+CREATE TABLE IF NOT EXISTS bundle_item (
+  id SERIAL PRIMARY KEY,
+  bundle_id INT NOT NULL REFERENCES bundle(id) ON DELETE CASCADE,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  quantity INT NOT NULL CHECK (quantity > 0),
+  UNIQUE (bundle_id, food_id)
+);
+
+CREATE TABLE IF NOT EXISTS bundle_slot (
+  id SERIAL PRIMARY KEY,
+  bundle_id INT NOT NULL REFERENCES bundle(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  quantity INT NOT NULL CHECK (quantity > 0),
+  sort_order INT NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_bundle_slot_bundle ON bundle_slot(bundle_id, sort_order);
+
+CREATE TABLE IF NOT EXISTS bundle_slot_choice (
+  slot_id INT NOT NULL REFERENCES bundle_slot(id) ON DELETE CASCADE,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  sort_order INT NOT NULL DEFAULT 0,
+  PRIMARY KEY (slot_id, food_id)
+);
+
+CREATE TABLE IF NOT EXISTS favorite_restaurant (
+  account_id UUID NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (account_id, restaurant_id)
+);
+
+CREATE TABLE IF NOT EXISTS favorite_food (
+  account_id UUID NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (account_id, food_id)
 );
 
 CREATE TABLE IF NOT EXISTS owner (
@@ -81,6 +301,33 @@ CREATE TABLE IF NOT EXISTS comments (
 );
 CREATE INDEX IF NOT EXISTS idx_comments_restaurant ON comments(restaurant_id);
 
+-- the food's price over time, a row is added whenever it changes
+CREATE TABLE IF NOT EXISTS food_price_history (
+  id SERIAL PRIMARY KEY,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  price INT NOT NULL,
+  changed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_food_price_history_food ON food_price_history(food_id, id);
+-- foods from before the history started get their current price as the first entry
+INSERT INTO food_price_history (food_id, price)
+SELECT food.id, food.price
+FROM food
+WHERE NOT EXISTS (SELECT 1 FROM food_price_history WHERE food_price_history.food_id = food.id);
+
+-- dish reviews, `food.rating` and `food.rating_count` are updated with every change
+CREATE TABLE IF NOT EXISTS food_review (
+  id SERIAL PRIMARY KEY,
+  food_id INT NOT NULL REFERENCES food(id) ON DELETE CASCADE,
+  account_id UUID NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+  rating INT NOT NULL CHECK (rating >= 1 AND rating <= 5),
+  text TEXT NOT NULL DEFAULT '',
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  UNIQUE (food_id, account_id)
+);
+ALTER TABLE food ADD COLUMN IF NOT EXISTS rating FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE food ADD COLUMN IF NOT EXISTS rating_count INT NOT NULL DEFAULT 0;
+
 CREATE TABLE IF NOT EXISTS comment_votes (
   account_id UUID NOT NULL REFERENCES account(id) ON DELETE CASCADE,
   comment_id INT NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
@@ -88,25 +335,95 @@ CREATE TABLE IF NOT EXISTS comment_votes (
   PRIMARY KEY (account_id, comment_id)
 );
 
+-- several intervals per day are allowed (split shifts), overlaps are rejected by the server
 CREATE TABLE IF NOT EXISTS restaurant_hours (
+  id SERIAL PRIMARY KEY,
   restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
   day_of_week weekday NOT NULL,
   open_time TIME NOT NULL,
   close_time TIME NOT NULL,
-  PRIMARY KEY (restaurant_id, day_of_week)
+  CONSTRAINT hours_not_empty
+    CHECK (open_time <> close_time)
 );
 
+-- databases created before split shifts were keyed by (restaurant_id, day_of_week)
+DO $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM information_schema.columns
+    WHERE table_name = 'restaurant_hours' AND column_name = 'id'
+  ) THEN
+    ALTER TABLE restaurant_hours DROP CONSTRAINT IF EXISTS restaurant_hours_pkey;
+    ALTER TABLE restaurant_hours ADD COLUMN id SERIAL PRIMARY KEY;
+  END IF;
+END$$;
+ALTER TABLE restaurant_hours ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+CREATE INDEX IF NOT EXISTS idx_restaurant_hours_day ON restaurant_hours(restaurant_id, day_of_week);
+
+CREATE TABLE IF NOT EXISTS restaurant_hours_exception (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  start_date DATE NOT NULL,
+  end_date DATE NOT NULL,
+  closed BOOLEAN NOT NULL DEFAULT TRUE,
+  open_time TIME,
+  close_time TIME,
+  reason TEXT,
+  CONSTRAINT exception_dates_order
+    CHECK (end_date >= start_date),
+  CONSTRAINT exception_hours_required
+    CHECK (closed OR (open_time IS NOT NULL AND close_time IS NOT NULL))
+);
+CREATE INDEX IF NOT EXISTS idx_hours_exception_restaurant ON restaurant_hours_exception(restaurant_id, start_date);
+
+-- either a radius band around restaurant.location or a polygon stored as [lat, lon, lat, lon, ...]
+CREATE TABLE IF NOT EXISTS delivery_zone (
+  id SERIAL PRIMARY KEY,
+  restaurant_id INT NOT NULL REFERENCES restaurant(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  radius_km DOUBLE PRECISION CHECK (radius_km > 0),
+  polygon DOUBLE PRECISION[],
+  fee INT NOT NULL CHECK (fee >= 0),
+  minimum_order INT NOT NULL DEFAULT 0,
+  estimated_minutes INT NOT NULL,
+  CONSTRAINT zone_shape_required
+    CHECK ((radius_km IS NULL) <> (polygon IS NULL))
+);
+CREATE INDEX IF NOT EXISTS idx_delivery_zone_restaurant ON delivery_zone(restaurant_id);
+
 
 
 CREATE TABLE IF NOT EXISTS orders (
   id SERIAL PRIMARY KEY,
   account_id UUID NOT NULL REFERENCES account(id) ON DELETE CASCADE,
-  status TEXT NOT NULL CHECK (status IN ('cart', 'pending', 'completed', 'canceled')),
+  status TEXT NOT NULL,
   total_price INT NOT NULL,
   total_discounted_price INT NOT NULL,
   created_at TIMESTAMP NOT NULL DEFAULT NOW()
 );
 
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS delivery_fee INT NOT NULL DEFAULT 0;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS delivery_zone_id INT REFERENCES delivery_zone(id) ON DELETE SET NULL;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS delivery_location DOUBLE PRECISION[2];
+
+-- see `OrderStatus` for the transitions
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check CHECK (status IN (
+  'cart', 'pending', 'accepted', 'preparing', 'ready', 'out_for_delivery', 'completed', 'canceled'
+));
+
+-- every status change with who made it, `actor_id` is empty for the system
+CREATE TABLE IF NOT EXISTS order_status_history (
+  id SERIAL PRIMARY KEY,
+  order_id INT NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  from_status TEXT NOT NULL,
+  to_status TEXT NOT NULL,
+  actor TEXT NOT NULL CHECK (actor IN ('customer', 'owner', 'system')),
+  actor_id UUID REFERENCES account(id) ON DELETE SET NULL,
+  changed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_order_status_history_order ON order_status_history(order_id, changed_at);
+
 CREATE INDEX IF NOT EXISTS orders_account_idx ON orders(account_id);
 CREATE INDEX IF NOT EXISTS orders_created_idx ON orders(created_at);
 
@@ -121,9 +438,44 @@ CREATE TABLE IF NOT EXISTS item (
     name TEXT NOT NULL,
     image TEXT NOT NULL,
     price INTEGER NOT NULL,
-    discount_price INTEGER,
-    UNIQUE(order_id, food_id)
+    discount_price INTEGER
+);
+
+-- the same food with other options is another item, `options_key` is the sorted option ids
+ALTER TABLE item ADD COLUMN IF NOT EXISTS options_price INT NOT NULL DEFAULT 0;
+ALTER TABLE item ADD COLUMN IF NOT EXISTS options_key TEXT NOT NULL DEFAULT '';
+ALTER TABLE item DROP CONSTRAINT IF EXISTS item_order_id_food_id_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_item_food_options ON item(order_id, food_id, options_key);
+
+-- the options of an item as they were chosen, later menu changes don't touch them
+CREATE TABLE IF NOT EXISTS item_option (
+  id SERIAL PRIMARY KEY,
+  item_id INT NOT NULL REFERENCES item(id) ON DELETE CASCADE,
+  option_id INT REFERENCES food_option(id) ON DELETE SET NULL,
+  group_name TEXT NOT NULL,
+  name TEXT NOT NULL,
+  price_delta INT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_item_option_item ON item_option(item_id);
+
+-- a bundle in the cart is one item with its foods in item_component, `options_key` then holds its choices
+-- orders keep the items of a deleted bundle, with neither `food_id` nor `bundle_id`
+ALTER TABLE item ALTER COLUMN food_id DROP NOT NULL;
+ALTER TABLE item ADD COLUMN IF NOT EXISTS bundle_id INT REFERENCES bundle(id) ON DELETE SET NULL;
+ALTER TABLE item DROP CONSTRAINT IF EXISTS item_food_or_bundle;
+ALTER TABLE item ADD CONSTRAINT item_food_or_bundle CHECK (food_id IS NULL OR bundle_id IS NULL);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_item_bundle_choices ON item(order_id, bundle_id, options_key);
+
+-- the foods of a bundle item as they were when it was added, `quantity` is per bundle
+CREATE TABLE IF NOT EXISTS item_component (
+  id SERIAL PRIMARY KEY,
+  item_id INT NOT NULL REFERENCES item(id) ON DELETE CASCADE,
+  food_id INT REFERENCES food(id) ON DELETE SET NULL,
+  slot_name TEXT,
+  name TEXT NOT NULL,
+  quantity INT NOT NULL
 );
+CREATE INDEX IF NOT EXISTS idx_item_component_item ON item_component(item_id);
 
 
 
@@ -17,6 +17,7 @@ pub enum Error {
     database_query_error(SqlxError),
     creating_upload_dir(std::io::Error),
     write_file(std::io::Error),
+    delete_file(std::io::Error),
     no_file,
     wrong_password,
     argon_library_error(argon2::Error),
@@ -26,6 +27,27 @@ pub enum Error {
     failed_to_get_account(SqlxError),
     cannot_decrypt_token,
     not_authorized,
+    invalid_hours,
+    overlapping_hours,
+    invalid_delivery_zone,
+    not_deliverable,
+    below_minimum_order(i32),
+    empty_cart,
+    multiple_restaurants,
+    invalid_date_range,
+    invalid_menu_file(String),
+    invalid_patch(String),
+    invalid_field(String),
+    precondition_failed,
+    precondition_required,
+    invalid_options(String),
+    out_of_stock(String),
+    not_purchased,
+    not_orderable(String),
+    invalid_status_change(String),
+    too_many_matches,
+    tag_exists(String),
+    prices_changed(String),
 }
 
 impl std::fmt::Display for Error {
@@ -36,6 +58,7 @@ impl std::fmt::Display for Error {
             Error::database_query_error(e) => write!(f, "Database error: {e}"),
             Error::creating_upload_dir(e) => write!(f, "Failed to create upload folder: {e}"),
             Error::write_file(e) => write!(f, "Failed to write file: {e}"),
+            Error::delete_file(e) => write!(f, "Failed to delete file: {e}"),
             Error::no_file => write!(f, "No file provided"),
             Error::wrong_password => write!(f, "Incorrect password"),
             Error::argon_library_error(e) => write!(f, "Password verification error: {e}"),
@@ -45,12 +68,40 @@ impl std::fmt::Display for Error {
             Error::failed_to_get_account(e) => write!(f, "Failed to login/get account: {e}"),
             Error::cannot_decrypt_token => write!(f, "invalid token"),
             Error::not_authorized => write!(f, "No permission to change the underlying resource"),
+            Error::invalid_hours => write!(f, "Open time and close time cannot be the same"),
+            Error::overlapping_hours => write!(f, "Opening hours overlap with another interval"),
+            Error::invalid_delivery_zone => write!(f, "A zone needs a positive radius or at least 3 polygon points"),
+            Error::not_deliverable => write!(f, "The restaurant doesn't deliver to this location"),
+            Error::below_minimum_order(min) => write!(f, "Minimum order for this location is {min}"),
+            Error::empty_cart => write!(f, "The cart is empty"),
+            Error::multiple_restaurants => write!(f, "An order can only contain food from one restaurant"),
+            Error::invalid_menu_file(e) => write!(f, "Invalid menu file: {e}"),
+            Error::invalid_patch(e) => write!(f, "Invalid patch: {e}"),
+            Error::invalid_field(e) => write!(f, "Invalid field: {e}"),
+            Error::precondition_failed => write!(f, "The resource was changed by someone else, fetch it again"),
+            Error::precondition_required => write!(f, "An If-Match header with the resource's ETag is required"),
+            Error::invalid_options(e) => write!(f, "Invalid options: {e}"),
+            Error::not_purchased => write!(f, "Only food from your completed orders can be reviewed"),
+            Error::invalid_status_change(e) => write!(f, "Can't change the order status: {e}"),
+            Error::not_orderable(name) => write!(f, "`{name}` can't be ordered at this time"),
+            Error::out_of_stock(name) => write!(f, "Not enough `{name}` left for today"),
+            Error::too_many_matches => write!(f, "Too many foods match to filter or sort by price, narrow the search with `q` or `city`"),
+            Error::tag_exists(slug) => write!(f, "A tag with the slug `{slug}` already exists"),
+            Error::prices_changed(names) => write!(f, "The price of {names} changed since it was added to the cart, check out again to order at the new prices"),
+            Error::invalid_date_range => write!(f, "`from` must be before `to` and the range can't be longer than a year"),
         }
     }
 }
 
 impl Reject for Error {}
 
+impl Error {
+    // a unique constraint was hit, so the caller can say which row already exists
+    pub fn is_duplicate_key(e: &SqlxError) -> bool {
+        matches!(e, SqlxError::Database(err) if err.code().unwrap_or_default() == DUPLICATE_KEY)
+    }
+}
+
 #[derive(Debug)]
 pub struct InvalidID;
 impl Reject for InvalidID {}
@@ -86,6 +137,11 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
                     ))
                 }
             }
+            // the id (or the pair of ids) in the path names nothing
+            sqlx::Error::RowNotFound => Ok(warp::reply::with_status(
+                "Not found".to_string(),
+                warp::http::StatusCode::NOT_FOUND,
+            )),
             _ => Ok(warp::reply::with_status(
                 "Database operation failed".to_string(),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
@@ -100,6 +156,27 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "Wrong credentials".to_string(),
             warp::http::StatusCode::UNAUTHORIZED,
         ))
+    } else if let Some(error @ Error::precondition_failed) = r.find() {
+        event!(Level::WARN, "If-Match doesn't match the current version");
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            warp::http::StatusCode::PRECONDITION_FAILED,
+        ))
+    } else if let Some(error @ Error::prices_changed(_)) = r.find() {
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            warp::http::StatusCode::CONFLICT,
+        ))
+    } else if let Some(error @ Error::tag_exists(_)) = r.find() {
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            warp::http::StatusCode::CONFLICT,
+        ))
+    } else if let Some(error @ Error::precondition_required) = r.find() {
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            warp::http::StatusCode::PRECONDITION_REQUIRED,
+        ))
     } else if let Some(error) = r.find::<Error>() {
         Ok(warp::reply::with_status(
             error.to_string(),
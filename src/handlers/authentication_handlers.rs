@@ -94,3 +94,9 @@ pub fn auth() -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Cl
         future::ready(Ok(token))
     })
 }
+
+// like `auth()` but never rejects, a missing or invalid token is just `None`
+pub fn optional_auth() -> impl Filter<Extract = (Option<Session>,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("Authorization")
+        .map(|token: Option<String>| token.and_then(|t| verify_token(t).ok()))
+}
@@ -0,0 +1,66 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{account::Session, bundle::NewBundle},
+};
+
+#[instrument]
+pub async fn get_bundles(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_bundles(id).await {
+        Ok(bundles) => bundles,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_bundle(
+    id: i32,
+    session: Session,
+    bundle: NewBundle,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_bundle(account_id, id, bundle).await {
+        Ok(bundle) => bundle,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("bundle added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn update_bundle(
+    id: i32,
+    bundle_id: i32,
+    session: Session,
+    bundle: NewBundle,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.update_bundle(account_id, id, bundle_id, bundle).await {
+        Ok(bundle) => bundle,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("bundle updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_bundle(
+    id: i32,
+    bundle_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_bundle(account_id, id, bundle_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("bundle deleted");
+    Ok(warp::reply::with_status(
+        "bundle deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
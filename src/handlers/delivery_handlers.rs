@@ -0,0 +1,81 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{
+        account::Session,
+        delivery::{DeliveryCheck, NewDeliveryZone},
+    },
+};
+
+#[instrument]
+pub async fn get_delivery_zones(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_delivery_zones(id).await {
+        Ok(zones) => zones,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_delivery_zone(
+    id: i32,
+    session: Session,
+    zone: NewDeliveryZone,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_delivery_zone(account_id, id, zone).await {
+        Ok(zone) => zone,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("delivery zone added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn update_delivery_zone(
+    id: i32,
+    zone_id: i32,
+    session: Session,
+    zone: NewDeliveryZone,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.update_delivery_zone(account_id, id, zone_id, zone).await {
+        Ok(zone) => zone,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("delivery zone updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_delivery_zone(
+    id: i32,
+    zone_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_delivery_zone(account_id, id, zone_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "delivery zone deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn check_deliverability(
+    id: i32,
+    check: DeliveryCheck,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.check_deliverability(id, check).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
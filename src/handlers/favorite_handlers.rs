@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+
+use crate::{
+    error::Error,
+    store::Store,
+    types::{
+        account::Session,
+        favorite::Favorites,
+        pagination::{extract_pagination, Pagination},
+    },
+};
+
+#[instrument]
+pub async fn get_favorites(
+    mut params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    // `kind` is optional, without it both lists are returned
+    let kind = params.remove("kind");
+    let mut pagination = Pagination::default();
+    if !params.is_empty() {
+        pagination = extract_pagination(params)?;
+    }
+
+    let mut res = Favorites::default();
+    match kind.as_deref() {
+        None | Some("restaurants") | Some("foods") => {}
+        Some(_) => return Err(warp::reject::custom(Error::parse_error)),
+    }
+    if kind.as_deref() != Some("foods") {
+        res.restaurants = match store
+            .get_favorite_restaurants(account_id, pagination.limit, pagination.offset)
+            .await
+        {
+            Ok(restaurants) => restaurants,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+    }
+    if kind.as_deref() != Some("restaurants") {
+        res.foods = match store
+            .get_favorite_foods(account_id, pagination.limit, pagination.offset)
+            .await
+        {
+            Ok(foods) => foods,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+    }
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_favorite_restaurant(
+    restaurant_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.add_favorite_restaurant(account_id, restaurant_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("favorite restaurant added");
+    Ok(warp::reply::with_status(
+        "restaurant added to favorites",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn delete_favorite_restaurant(
+    restaurant_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_favorite_restaurant(account_id, restaurant_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "restaurant removed from favorites",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn add_favorite_food(
+    food_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.add_favorite_food(account_id, food_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("favorite food added");
+    Ok(warp::reply::with_status(
+        "food added to favorites",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn delete_favorite_food(
+    food_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_favorite_food(account_id, food_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "food removed from favorites",
+        warp::http::StatusCode::OK,
+    ))
+}
@@ -1,6 +1,14 @@
+use std::collections::HashMap;
+
+use tracing::{event, Level};
 use warp::{filters::multipart::FormData, reject::Rejection, reply::Reply};
 
-use crate::{error::Error, store::Store, types::account::Session, utils::upload_file::upload_file};
+use crate::{
+    error::Error,
+    store::Store,
+    types::{account::Session, gallery::UpdateRestaurantImage},
+    utils::upload_file::{delete_file, upload_file},
+};
 
 pub async fn restaurant_pfp_handler(
     id: i32,
@@ -17,7 +25,9 @@ pub async fn restaurant_pfp_handler(
             Ok(url) => url,
             Err(e) => return Err(e),
         };
-        if let Err(e) = store.insert_file_to_restaurant(&public_url, id).await {
+        // the profile picture is the cover of the gallery, older pictures stay in the gallery
+        if let Err(e) = store.add_restaurant_image(id, &public_url, None, true).await {
+            remove_orphan(&public_url).await;
             return Err(warp::reject::custom(e));
         }
 
@@ -60,4 +70,116 @@ pub async fn food_image_handler(
     } else {
         Err(warp::reject::custom(Error::not_authorized))
     }
-}
\ No newline at end of file
+}
+
+// used when the database rejects a file we already wrote
+async fn remove_orphan(public_url: &str) {
+    if let Err(e) = delete_file(public_url).await {
+        event!(Level::ERROR, "{}", e);
+    }
+}
+
+pub async fn get_restaurant_images(id: i32, store: Store) -> Result<impl Reply, Rejection> {
+    let res = match store.get_restaurant_images(id).await {
+        Ok(images) => images,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+pub async fn restaurant_image_handler(
+    id: i32,
+    params: HashMap<String, String>,
+    form: FormData,
+    session: Session,
+    store: Store,
+) -> Result<impl Reply, Rejection> {
+    let account_id = session.account_id;
+    if store
+        .verify_restaurant_modification_access(id, &account_id)
+        .await?
+    {
+        let caption = params.get("caption").cloned();
+        let public_url = match upload_file("restaurant_gallery".to_string(), form).await {
+            Ok(url) => url,
+            Err(e) => return Err(e),
+        };
+        match store.add_restaurant_image(id, &public_url, caption, false).await {
+            Ok(image) => Ok(warp::reply::json(&image)),
+            Err(e) => {
+                remove_orphan(&public_url).await;
+                Err(warp::reject::custom(e))
+            }
+        }
+    } else {
+        Err(warp::reject::custom(Error::not_authorized))
+    }
+}
+
+pub async fn update_restaurant_image(
+    id: i32,
+    image_id: i32,
+    session: Session,
+    update: UpdateRestaurantImage,
+    store: Store,
+) -> Result<impl Reply, Rejection> {
+    let account_id = session.account_id;
+    if store
+        .verify_restaurant_modification_access(id, &account_id)
+        .await?
+    {
+        match store.update_restaurant_image(id, image_id, update).await {
+            Ok(image) => Ok(warp::reply::json(&image)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(Error::not_authorized))
+    }
+}
+
+pub async fn reorder_restaurant_images(
+    id: i32,
+    session: Session,
+    image_ids: Vec<i32>,
+    store: Store,
+) -> Result<impl Reply, Rejection> {
+    let account_id = session.account_id;
+    if store
+        .verify_restaurant_modification_access(id, &account_id)
+        .await?
+    {
+        match store.reorder_restaurant_images(id, image_ids).await {
+            Ok(images) => Ok(warp::reply::json(&images)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(Error::not_authorized))
+    }
+}
+
+pub async fn delete_restaurant_image(
+    id: i32,
+    image_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl Reply, Rejection> {
+    let account_id = session.account_id;
+    if store
+        .verify_restaurant_modification_access(id, &account_id)
+        .await?
+    {
+        let url = match store.delete_restaurant_image(id, image_id).await {
+            Ok(url) => url,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+        if let Err(e) = delete_file(&url).await {
+            return Err(warp::reject::custom(e));
+        }
+        Ok(warp::reply::with_status(
+            "image deleted",
+            warp::http::StatusCode::OK,
+        ))
+    } else {
+        Err(warp::reject::custom(Error::not_authorized))
+    }
+}
@@ -1,22 +1,138 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
 use tracing::{info, instrument};
+use warp::hyper::body::Bytes;
 
 use crate::{
+    error::Error,
     store::Store,
     types::{
         account::Session,
+        availability::ScheduleTarget,
+        bundle::Bundle,
+        dietary::DietaryFilter,
+        food_search::FoodSearch,
         food::{Food, NewFood},
+        food_option::{MenuFood, NewOptionGroup},
+        restaurant::HoursInterval,
+        section::{MenuGroup, MenuSort},
+    },
+    utils::{
+        cache::{cached_reply, CacheKey, CachePolicy, CachedResponse, Conditional},
+        etag::{version_etag, IfMatch},
+        merge_patch::{apply_patch, parse_patch},
     },
 };
 
+// the foods and bundles that pass the filter, grouped by section
+async fn load_menu(
+    store: &Store,
+    id: i32,
+    filter: &DietaryFilter,
+    sort: MenuSort,
+) -> Result<Vec<MenuGroup>, Error> {
+    let sections = store.get_menu_sections(id).await?;
+    let mut foods = store.get_menu(id).await?;
+    sort.sort(&mut foods);
+    let mut option_groups = store.get_menu_option_groups(id).await?;
+    let mut bundles = store.get_bundles(id).await?;
+    if !filter.is_empty() {
+        let by_id = foods.iter().map(|food| (food.id, food)).collect();
+        bundles = bundles
+            .into_iter()
+            .filter_map(|bundle: Bundle| bundle.filter(filter, &by_id))
+            .collect();
+    }
+    let foods = foods
+        .into_iter()
+        .filter(|food| filter.matches(food))
+        .map(|food| MenuFood {
+            option_groups: option_groups.remove(&food.id).unwrap_or_default(),
+            food,
+        })
+        .collect();
+    Ok(MenuGroup::group(sections, foods, bundles))
+}
+
+// prices and `orderable_now` change by themselves when a promotion or an availability window
+// starts or ends, the menu is only good until the next of those
+async fn menu_expires_at(store: &Store, id: i32) -> Result<Option<DateTime<Utc>>, Error> {
+    let now = Local::now().naive_local();
+    let promotion = store.next_promotion_change(id, now).await?;
+    let schedule = store.next_schedule_change(id, now).await?;
+    Ok(promotion
+        .into_iter()
+        .chain(schedule)
+        .min()
+        .map(|changes_at| Utc::now() + (changes_at - now)))
+}
+
+// only the unfiltered menu in menu order is kept in the cache
 #[instrument]
-pub async fn get_menu(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = match store.get_menu(id).await {
-        Ok(food) => food,
+pub async fn get_menu(
+    id: i32,
+    params: HashMap<String, String>,
+    conditional: Conditional,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let filter = DietaryFilter::from_params(&params)?;
+    let sort = MenuSort::from_param(params.get("sort").map(String::as_str))?;
+    let cacheable = filter.is_empty() && sort == MenuSort::Menu;
+    let key = CacheKey::Menu(id);
+    let res = match store.cache.get(&key).filter(|_| cacheable) {
+        Some(res) => res,
+        None => {
+            let menu = match load_menu(&store, id, &filter, sort).await {
+                Ok(menu) => menu,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            let expires_at = match menu_expires_at(&store, id).await {
+                Ok(expires_at) => expires_at,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            let res = CachedResponse::new(&menu, None)?.expiring_at(expires_at);
+            if cacheable {
+                store.cache.insert(key, res.clone());
+            }
+            res
+        }
+    };
+    let cache_control = CachePolicy::cache_control(res.max_age(store.cache.policy.menu));
+    Ok(cached_reply(&res, &conditional, &cache_control))
+}
+
+// `GET /food/search?q=...`, dishes of every restaurant with a summary of the restaurant
+#[instrument]
+pub async fn search_food(
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let search = FoodSearch::from_params(&params)?;
+    let res = match store.search_food(&search).await {
+        Ok(results) => results,
         Err(e) => return Err(warp::reject::custom(e)),
     };
     Ok(warp::reply::json(&res))
 }
 
+#[instrument]
+pub async fn get_food(
+    restaurant_id: i32,
+    food_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_food(restaurant_id, food_id).await {
+        Ok(food) => food,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::with_header(
+        warp::reply::json(&res),
+        "etag",
+        version_etag(res.version),
+    ))
+}
+
 #[instrument]
 pub async fn post_new_food(
     session: Session,
@@ -37,27 +153,33 @@ pub async fn post_new_food(
 #[instrument]
 pub async fn update_food(
     session: Session,
+    if_match: IfMatch,
     food: Food,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let res = match store.update_food(food, account_id).await {
+    let res = match store.update_food(food, account_id, &if_match).await {
         Ok(food) => food,
         Err(e) => return Err(warp::reject::custom(e)),
     };
     info!("food updated");
-    Ok(warp::reply::json(&res))
+    Ok(warp::reply::with_header(
+        warp::reply::json(&res),
+        "etag",
+        version_etag(res.version),
+    ))
 }
 
 #[instrument]
 pub async fn delete_food(
     session: Session,
+    if_match: IfMatch,
     food: Food,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
 
-    if let Err(e) = store.delete_food(food, account_id).await {
+    if let Err(e) = store.delete_food(food, account_id, &if_match).await {
         return Err(warp::reject::custom(e));
     }
     Ok(warp::reply::with_status(
@@ -65,3 +187,127 @@ pub async fn delete_food(
         warp::http::StatusCode::OK,
     ))
 }
+
+// json merge patch, only the sent fields change
+#[instrument(skip(body))]
+pub async fn patch_food(
+    restaurant_id: i32,
+    food_id: i32,
+    session: Session,
+    if_match: IfMatch,
+    body: Bytes,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store
+        .verify_restaurant_modification_access(restaurant_id, &account_id)
+        .await?
+    {
+        return Err(warp::reject::custom(Error::not_authorized));
+    }
+
+    let patch = parse_patch(&body)?;
+    let current = match store.get_food(restaurant_id, food_id).await {
+        Ok(food) => food,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let mut food = apply_patch(&current, &patch, &["id", "restaurant_id", "sort_order", "version", "effective_price", "promotion_id", "rating", "rating_count", "orderable_now"])?;
+    // a new free text tag unlinks the managed tag, otherwise the slug would win
+    if patch.contains_key("tag") && !patch.contains_key("tag_id") {
+        food.tag_id = None;
+    }
+    food.validate()?;
+
+    let res = match store.update_food(food, account_id, &if_match).await {
+        Ok(food) => food,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("food patched");
+    Ok(warp::reply::with_header(
+        warp::reply::json(&res),
+        "etag",
+        version_etag(res.version),
+    ))
+}
+
+#[instrument]
+pub async fn get_food_options(
+    restaurant_id: i32,
+    food_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // the food has to be on this restaurant's menu
+    if let Err(e) = store.get_food(restaurant_id, food_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    match store.get_option_groups(food_id).await {
+        Ok(groups) => Ok(warp::reply::json(&groups)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn get_price_history(
+    restaurant_id: i32,
+    food_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_price_history(restaurant_id, food_id).await {
+        Ok(history) => Ok(warp::reply::json(&history)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn get_food_schedule(
+    restaurant_id: i32,
+    food_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_schedule(restaurant_id, ScheduleTarget::Food(food_id)).await {
+        Ok(week) => Ok(warp::reply::json(&week)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+// replaces the availability windows of the food, an empty list makes it orderable any time
+#[instrument]
+pub async fn replace_food_schedule(
+    restaurant_id: i32,
+    food_id: i32,
+    session: Session,
+    week: Vec<HoursInterval>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    match store
+        .replace_schedule(account_id, restaurant_id, ScheduleTarget::Food(food_id), week)
+        .await
+    {
+        Ok(week) => {
+            info!("food schedule replaced");
+            Ok(warp::reply::json(&week))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+// replaces all option groups of the food
+#[instrument]
+pub async fn replace_food_options(
+    restaurant_id: i32,
+    food_id: i32,
+    session: Session,
+    groups: Vec<NewOptionGroup>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    match store
+        .replace_option_groups(account_id, restaurant_id, food_id, groups)
+        .await
+    {
+        Ok(groups) => {
+            info!("food options replaced");
+            Ok(warp::reply::json(&groups))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
@@ -1,23 +1,55 @@
+use chrono::Local;
 use tracing::{info, instrument};
 
-use crate::{store::Store, types::{account::Session, restaurant::OpenHours}};
+use crate::{
+    store::Store,
+    types::{
+        account::Session,
+        language::Language,
+        restaurant::{HoursException, HoursInterval, NewHoursException, NewOpenHours, OpenHours},
+    },
+    utils::{
+        cache::{cached_reply, CacheKey, CachePolicy, CachedResponse, Conditional},
+        etag::IfMatch,
+    },
+};
 
+// one cache entry per language, the etag is the week's so it works for `If-Match` too
 #[instrument]
 pub async fn get_restaurant_hours(
     id: i32,
+    accept_language: Option<String>,
+    conditional: Conditional,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = match store.get_restaurant_hours(id).await {
-        Ok(hours) => hours,
-        Err(e) => return Err(warp::reject::custom(e)),
+    let language = Language::from_accept_language(accept_language);
+    let key = CacheKey::Hours(id, language);
+    let res = match store.cache.get(&key) {
+        Some(res) => res,
+        None => {
+            let hours = match store.get_restaurant_hours(id).await {
+                Ok(hours) => hours,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            let etag = OpenHours::week_etag(&hours);
+            let hours: Vec<_> = hours.iter().map(|hours| hours.localized(language)).collect();
+            let res = CachedResponse::new(&hours, Some(etag))?;
+            store.cache.insert(key, res.clone());
+            res
+        }
     };
-    Ok(warp::reply::json(&res))
+    let cache_control = CachePolicy::cache_control(store.cache.policy.hours);
+    Ok(warp::reply::with_header(
+        cached_reply(&res, &conditional, &cache_control),
+        "vary",
+        "accept-language",
+    ))
 }
 
 #[instrument]
 pub async fn post_restaurant_hours(
     session: Session,
-    hours: OpenHours,
+    hours: NewOpenHours,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
@@ -34,27 +66,35 @@ pub async fn post_restaurant_hours(
 #[instrument]
 pub async fn put_restaurant_hours(
     session: Session,
+    if_match: IfMatch,
+    accept_language: Option<String>,
     hours: OpenHours,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let res = match store.put_restaurant_hours(account_id, hours).await {
-        Ok(hours) => hours,
+    let language = Language::from_accept_language(accept_language);
+    let (res, etag) = match store.put_restaurant_hours(account_id, hours, &if_match).await {
+        Ok(res) => res,
         Err(e) => return Err(warp::reject::custom(e)),
     };
     info!("hours updated");
-    Ok(warp::reply::json(&res))
+    Ok(warp::reply::with_header(
+        warp::reply::json(&res.localized(language)),
+        "etag",
+        etag,
+    ))
 }
 
 #[instrument]
 pub async fn delete_restaurant_hours(
     session: Session,
+    if_match: IfMatch,
     hours: OpenHours,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
 
-    if let Err(e) = store.delete_restaurant_hours(account_id, hours).await {
+    if let Err(e) = store.delete_restaurant_hours(account_id, hours, &if_match).await {
         return Err(warp::reject::custom(e));
     }
     Ok(warp::reply::with_status(
@@ -62,3 +102,94 @@ pub async fn delete_restaurant_hours(
         warp::http::StatusCode::OK,
     ))
 }
+
+#[instrument]
+pub async fn replace_restaurant_hours(
+    id: i32,
+    session: Session,
+    if_match: IfMatch,
+    accept_language: Option<String>,
+    week: Vec<HoursInterval>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let language = Language::from_accept_language(accept_language);
+    let res = match store.replace_restaurant_hours(account_id, id, week, &if_match).await {
+        Ok(hours) => hours,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("weekly hours replaced");
+    let etag = OpenHours::week_etag(&res);
+    let res: Vec<_> = res.iter().map(|hours| hours.localized(language)).collect();
+    Ok(warp::reply::with_header(warp::reply::json(&res), "etag", etag))
+}
+
+#[instrument]
+pub async fn get_open_status(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store
+        .get_open_status(id, Local::now().naive_local())
+        .await
+    {
+        Ok(status) => status,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn get_hours_exceptions(
+    id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_hours_exceptions(id).await {
+        Ok(exceptions) => exceptions,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn post_hours_exception(
+    session: Session,
+    exception: NewHoursException,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.post_hours_exception(account_id, exception).await {
+        Ok(exception) => exception,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("hours exception added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn put_hours_exception(
+    session: Session,
+    exception: HoursException,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.put_hours_exception(account_id, exception).await {
+        Ok(exception) => exception,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("hours exception updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_hours_exception(
+    session: Session,
+    id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_hours_exception(account_id, id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "hours exception deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
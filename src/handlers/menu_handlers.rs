@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+use warp::{http::StatusCode, hyper::body::Bytes};
+
+use crate::{
+    error::Error,
+    store::Store,
+    types::{
+        account::Session,
+        menu::{parse_menu, write_menu, MenuFormat},
+    },
+};
+
+// `?format=csv|json&dry_run=true`, the body is the file itself
+#[instrument(skip(body))]
+pub async fn import_menu(
+    restaurant_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    body: Bytes,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store
+        .verify_restaurant_modification_access(restaurant_id, &account_id)
+        .await?
+    {
+        return Err(warp::reject::custom(Error::not_authorized));
+    }
+
+    let format = MenuFormat::from_param(params.get("format").map(String::as_str))?;
+    let dry_run = params.get("dry_run").is_some_and(|v| v == "true");
+    let rows = parse_menu(format, &body)?;
+
+    let report = match store.import_menu(restaurant_id, rows, dry_run).await {
+        Ok(report) => report,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    if report.applied {
+        info!("menu imported");
+    }
+    // a dry run always succeeds, the report is the answer
+    let status = match report.errors.is_empty() || dry_run {
+        true => StatusCode::OK,
+        false => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+#[instrument]
+pub async fn export_menu(
+    restaurant_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store
+        .verify_restaurant_modification_access(restaurant_id, &account_id)
+        .await?
+    {
+        return Err(warp::reject::custom(Error::not_authorized));
+    }
+
+    let format = MenuFormat::from_param(params.get("format").map(String::as_str))?;
+    let rows = match store.export_menu(restaurant_id).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let file = write_menu(format, &rows)?;
+    let file_name = format!("menu-{restaurant_id}.{}", format.extension());
+    Ok(warp::reply::with_header(
+        warp::reply::with_header(file, "content-type", format.content_type()),
+        "content-disposition",
+        format!("attachment; filename=\"{file_name}\""),
+    ))
+}
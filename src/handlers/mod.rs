@@ -1,8 +1,18 @@
+pub mod bundle_handlers;
 pub mod comment_handlers;
+pub mod delivery_handlers;
+pub mod favorite_handlers;
 pub mod food_handlers;
 pub mod restaurant_handlers;
+pub mod review_handlers;
 pub mod files_handlers;
 pub mod authentication_handlers;
 pub mod hours_handler;
+pub mod menu_handlers;
 pub mod order_handlers;
-pub mod owner_handlers;
\ No newline at end of file
+pub mod owner_handlers;
+pub mod promotion_handlers;
+pub mod recommendation_handlers;
+pub mod section_handlers;
+pub mod stock_handlers;
+pub mod tag_handlers;
\ No newline at end of file
@@ -1,8 +1,12 @@
-use tracing::instrument;
+use tracing::{info, instrument};
 
 use crate::{
     store::Store,
-    types::{account::Session, orders::NewItem},
+    types::{
+        account::Session,
+        delivery::DeliveryAddress,
+        orders::{NewItem, NewOrderStatus},
+    },
 };
 
 #[instrument]
@@ -49,3 +53,92 @@ pub async fn add_to_cart(
         warp::http::StatusCode::OK,
     ))
 }
+
+#[instrument]
+pub async fn remove_cart_item(
+    session: Session,
+    item_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.remove_cart_item(account_id, item_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "cart updated!",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn set_order_delivery(
+    session: Session,
+    order_id: i32,
+    address: DeliveryAddress,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.set_order_delivery(account_id, order_id, address).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn clear_order_delivery(
+    session: Session,
+    order_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.clear_order_delivery(account_id, order_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "cart updated!",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn checkout(session: Session, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.checkout(account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("order checked out");
+    Ok(warp::reply::json(&res))
+}
+
+// customers cancel, restaurants move the order along, see `OrderStatus::can_change_to`
+#[instrument]
+pub async fn set_order_status(
+    session: Session,
+    order_id: i32,
+    status: NewOrderStatus,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.set_order_status(account_id, order_id, status.status).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("order status changed");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn get_order_status_history(
+    session: Session,
+    order_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.get_order_status_history(account_id, order_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use tracing::instrument;
 
 use crate::{
@@ -5,9 +7,14 @@ use crate::{
     types::{
         account::Session,
         owner::{NewOwner, Owner},
+        pagination::extract_params,
+        stats::extract_stats_range,
     },
 };
 
+const DEFAULT_TOP_FOODS: i32 = 10;
+const MAX_TOP_FOODS: i32 = 100;
+
 #[instrument]
 pub async fn get_owner(
     session: Session,
@@ -68,3 +75,55 @@ pub async fn replace_owner(
         warp::http::StatusCode::OK,
     ))
 }
+
+#[instrument]
+pub async fn get_sales_stats(
+    restaurant_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let range = extract_stats_range(&params)?;
+    let res = match store.get_sales_stats(account_id, restaurant_id, range).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn get_top_foods(
+    restaurant_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let range = extract_stats_range(&params)?;
+    let limit = match params.contains_key("limit") {
+        true => extract_params::<i32>(&params, "limit")?.clamp(1, MAX_TOP_FOODS),
+        false => DEFAULT_TOP_FOODS,
+    };
+    let res = match store.get_top_foods(account_id, restaurant_id, range, limit).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn get_rating_stats(
+    restaurant_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let range = extract_stats_range(&params)?;
+    let res = match store.get_rating_stats(account_id, restaurant_id, range).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
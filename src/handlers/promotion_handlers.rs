@@ -0,0 +1,66 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{account::Session, promotion::NewPromotion},
+};
+
+#[instrument]
+pub async fn get_promotions(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_promotions(id).await {
+        Ok(promotions) => promotions,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_promotion(
+    id: i32,
+    session: Session,
+    promotion: NewPromotion,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_promotion(account_id, id, promotion).await {
+        Ok(promotion) => promotion,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("promotion added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn update_promotion(
+    id: i32,
+    promotion_id: i32,
+    session: Session,
+    promotion: NewPromotion,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.update_promotion(account_id, id, promotion_id, promotion).await {
+        Ok(promotion) => promotion,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("promotion updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_promotion(
+    id: i32,
+    promotion_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_promotion(account_id, id, promotion_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("promotion deleted");
+    Ok(warp::reply::with_status(
+        "promotion deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
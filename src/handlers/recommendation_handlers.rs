@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use tracing::instrument;
+
+use crate::{
+    store::Store,
+    types::{account::Session, pagination::extract_params},
+};
+
+const DEFAULT_RECOMMENDATIONS: i32 = 20;
+const MAX_RECOMMENDATIONS: i32 = 50;
+
+#[instrument]
+pub async fn get_recommendations(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let city = params.get("city").cloned();
+    let limit = match params.contains_key("limit") {
+        true => extract_params::<i32>(&params, "limit")?.clamp(1, MAX_RECOMMENDATIONS),
+        false => DEFAULT_RECOMMENDATIONS,
+    };
+    let res = match store.get_recommendations(account_id, city, limit).await {
+        Ok(recommendations) => recommendations,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
@@ -1,15 +1,24 @@
 use std::collections::HashMap;
 
+use serde_json::Value;
 use tracing::{event, info, instrument, Level};
+use warp::{hyper::body::Bytes, Reply};
 
 use crate::{
     error::Error,
     store::Store,
     types::{
         account::{Role, Session},
+        dietary::DietaryFilter,
         pagination::{extract_pagination, extract_params, Pagination},
         restaurant::{NewRestaurant, Restaurant},
     },
+    utils::{
+        cache::{cached_reply, CacheKey, CachePolicy, CachedResponse, Conditional, PRIVATE_CACHE_CONTROL},
+        etag::{version_etag, IfMatch},
+        merge_patch::{apply_patch, parse_patch},
+        upload_file::delete_file,
+    },
     verify_roles,
 };
 
@@ -36,54 +45,103 @@ pub async fn create_restaurant(
     }
 }
 
+// anonymous requests are served from the response cache, `is_favorite` makes the others private
 #[instrument]
 pub async fn get_single_restaurant(
     id: i32,
+    session: Option<Session>,
+    conditional: Conditional,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = match store.get_single_restaurant(id).await {
-        Ok(restaurant) => restaurant,
-        Err(e) => return Err(warp::reject::custom(e)),
+    if let Some(session) = session {
+        let res = match store.get_single_restaurant(id, Some(session.account_id)).await {
+            Ok(restaurant) => restaurant,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+        let reply = warp::reply::with_header(
+            warp::reply::json(&res),
+            "etag",
+            version_etag(res.version),
+        );
+        return Ok(warp::reply::with_header(reply, "cache-control", PRIVATE_CACHE_CONTROL).into_response());
+    }
+
+    let key = CacheKey::Restaurant(id);
+    let res = match store.cache.get(&key) {
+        Some(res) => res,
+        None => {
+            let restaurant = match store.get_single_restaurant(id, None).await {
+                Ok(restaurant) => restaurant,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            // `rating` comes from the comments and doesn't bump `version`, so the etag is the body's
+            // the etag for `If-Match` is sent to authenticated requests
+            let res = CachedResponse::new(&restaurant, None)?;
+            store.cache.insert(key, res.clone());
+            res
+        }
     };
-    Ok(warp::reply::json(&res))
+    let cache_control = CachePolicy::cache_control(store.cache.policy.restaurants);
+    Ok(cached_reply(&res, &conditional, &cache_control))
 }
 
 #[instrument]
 pub async fn get_restaurants(
     params: HashMap<String, String>,
+    session: Option<Session>,
+    conditional: Conditional,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.map(|s| s.account_id);
     event!(target: "restaurant_api", Level::INFO, "querying restaurants");
     let mut pagination = Pagination::default();
     if !params.is_empty() {
         event!(Level::INFO, pagination = true);
         pagination = extract_pagination(params)?;
-
-        let res = match store
-            .get_restaurants(pagination.limit, pagination.offset)
-            .await
-        {
-            Ok(res) => res,
-            Err(e) => return Err(warp::reject::custom(e)),
-        };
-        Ok(warp::reply::json(&res))
     } else {
         event!(Level::INFO, pagination = false);
+    }
+
+    if account_id.is_some() {
         let res = match store
-            .get_restaurants(pagination.limit, pagination.offset)
+            .get_restaurants(pagination.limit, pagination.offset, account_id)
             .await
         {
             Ok(res) => res,
             Err(e) => return Err(warp::reject::custom(e)),
         };
-        Ok(warp::reply::json(&res))
+        let reply = warp::reply::json(&res);
+        return Ok(warp::reply::with_header(reply, "cache-control", PRIVATE_CACHE_CONTROL).into_response());
     }
+
+    let key = CacheKey::Restaurants {
+        limit: pagination.limit,
+        offset: pagination.offset,
+    };
+    let res = match store.cache.get(&key) {
+        Some(res) => res,
+        None => {
+            let restaurants = match store
+                .get_restaurants(pagination.limit, pagination.offset, None)
+                .await
+            {
+                Ok(res) => res,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            let res = CachedResponse::new(&restaurants, None)?;
+            store.cache.insert(key, res.clone());
+            res
+        }
+    };
+    let cache_control = CachePolicy::cache_control(store.cache.policy.restaurants);
+    Ok(cached_reply(&res, &conditional, &cache_control))
 }
 
 #[instrument]
 pub async fn update_restaurant(
     id: i32,
     session: Session,
+    if_match: IfMatch,
     store: Store,
     restaurant: Restaurant,
 ) -> Result<impl warp::Reply, warp::Rejection> {
@@ -93,21 +151,69 @@ pub async fn update_restaurant(
         .verify_restaurant_modification_access(id, &account_id)
         .await?
     {
-        let res = match store.update_restaurant(restaurant, id).await {
+        let res = match store.update_restaurant(restaurant, id, &if_match).await {
             Ok(restaurant) => restaurant,
             Err(e) => return Err(warp::reject::custom(e)),
         };
         info!("restaurant updated");
-        Ok(warp::reply::json(&res))
+        Ok(warp::reply::with_header(
+            warp::reply::json(&res),
+            "etag",
+            version_etag(res.version),
+        ))
     } else {
         Err(warp::reject::custom(Error::not_authorized))
     }
 }
 
+// json merge patch, only the sent fields change
+#[instrument(skip(body))]
+pub async fn patch_restaurant(
+    id: i32,
+    session: Session,
+    if_match: IfMatch,
+    body: Bytes,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store
+        .verify_restaurant_modification_access(id, &account_id)
+        .await?
+    {
+        return Err(warp::reject::custom(Error::not_authorized));
+    }
+
+    let patch = parse_patch(&body)?;
+    let current = match store.get_single_restaurant(id, None).await {
+        Ok(restaurant) => restaurant,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let mut restaurant = apply_patch(&current, &patch, &["id", "rating", "is_favorite", "version", "image", "tags"])?;
+    // tags are only rewritten when the patch asks for it, `"tag_ids": null` removes all of them
+    restaurant.tag_ids = match patch.get("tag_ids") {
+        None => None,
+        Some(Value::Null) => Some(Vec::new()),
+        Some(_) => restaurant.tag_ids,
+    };
+    restaurant.validate()?;
+
+    let res = match store.update_restaurant(restaurant, id, &if_match).await {
+        Ok(restaurant) => restaurant,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("restaurant patched");
+    Ok(warp::reply::with_header(
+        warp::reply::json(&res),
+        "etag",
+        version_etag(res.version),
+    ))
+}
+
 #[instrument]
 pub async fn delete_restaurant(
     id: i32,
     session: Session,
+    if_match: IfMatch,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
@@ -116,9 +222,19 @@ pub async fn delete_restaurant(
         .verify_restaurant_modification_access(id, &account_id)
         .await?
     {
-        if let Err(e) = store.delete_restaurant(id).await {
+        // the rows go away with the restaurant (ON DELETE CASCADE), the files have to be removed by hand
+        let images = match store.get_restaurant_images(id).await {
+            Ok(images) => images,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+        if let Err(e) = store.delete_restaurant(id, &if_match).await {
             return Err(warp::reject::custom(e));
         }
+        for image in images {
+            if let Err(e) = delete_file(&image.url).await {
+                event!(Level::ERROR, "{}", e);
+            }
+        }
         Ok(warp::reply::with_status(
             format!("Restaurant {} deleted", id),
             warp::http::StatusCode::OK,
@@ -131,9 +247,32 @@ pub async fn delete_restaurant(
 #[instrument]
 pub async fn search_by_city(
     city: String,
+    session: Option<Session>,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = match store.search_by_city(city).await {
+    let account_id = session.map(|s| s.account_id);
+    let res = match store.search_by_city(city, account_id).await {
+        Ok(restaurants) => restaurants,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+// `GET /restaurants/dietary?diet=vegan&exclude_allergen=nuts&city=...`
+#[instrument]
+pub async fn search_by_dietary(
+    params: HashMap<String, String>,
+    session: Option<Session>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let filter = DietaryFilter::from_params(&params)?;
+    if filter.is_empty() {
+        return Err(warp::reject::custom(Error::missing_parameters));
+    }
+    let city = params.get("city").cloned();
+    let account_id = session.map(|s| s.account_id);
+    let res = match store.search_by_dietary(&filter, city, account_id).await {
         Ok(restaurants) => restaurants,
         Err(e) => return Err(warp::reject::custom(e)),
     };
@@ -142,13 +281,14 @@ pub async fn search_by_city(
 }
 
 #[instrument]
-pub async fn search_by_tag(params: HashMap<String, String>, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+pub async fn search_by_tag(params: HashMap<String, String>, session: Option<Session>, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
     // check out for these guys
     // TEST: error propagations
     // TEST: persian input
     let tag = extract_params::<String>(&params, "tag")?;
     let city = extract_params::<String>(&params, "city")?;
-    let res = match store.search_by_tag(tag, city).await {
+    let account_id = session.map(|s| s.account_id);
+    let res = match store.search_by_tag(tag, city, account_id).await {
         Ok(restaurants) => restaurants,
         Err(e) => return Err(warp::reject::custom(e)),
     };
@@ -0,0 +1,54 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{account::Session, review::NewFoodReview},
+};
+
+#[instrument]
+pub async fn get_food_reviews(
+    id: i32,
+    food_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_food_reviews(id, food_id).await {
+        Ok(reviews) => reviews,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_food_review(
+    id: i32,
+    food_id: i32,
+    session: Session,
+    review: NewFoodReview,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_food_review(account_id, id, food_id, review).await {
+        Ok(review) => review,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("food review added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_food_review(
+    id: i32,
+    food_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_food_review(account_id, id, food_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("food review deleted");
+    Ok(warp::reply::with_status(
+        "review deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
@@ -0,0 +1,136 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{
+        account::Session, availability::ScheduleTarget, restaurant::HoursInterval,
+        section::NewMenuSection,
+    },
+};
+
+#[instrument]
+pub async fn get_menu_sections(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_menu_sections(id).await {
+        Ok(sections) => sections,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_menu_section(
+    id: i32,
+    session: Session,
+    section: NewMenuSection,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_menu_section(account_id, id, section).await {
+        Ok(section) => section,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("menu section added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn update_menu_section(
+    id: i32,
+    section_id: i32,
+    session: Session,
+    section: NewMenuSection,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.update_menu_section(account_id, id, section_id, section).await {
+        Ok(section) => section,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("menu section updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_menu_section(
+    id: i32,
+    section_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_menu_section(account_id, id, section_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "menu section deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn reorder_menu_sections(
+    id: i32,
+    session: Session,
+    section_ids: Vec<i32>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.reorder_menu_sections(account_id, id, section_ids).await {
+        Ok(sections) => sections,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn get_section_schedule(
+    id: i32,
+    section_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = match store.get_schedule(id, ScheduleTarget::Section(section_id)).await {
+        Ok(week) => week,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+// the windows apply to every food of the section, on top of the food's own
+#[instrument]
+pub async fn replace_section_schedule(
+    id: i32,
+    section_id: i32,
+    session: Session,
+    week: Vec<HoursInterval>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store
+        .replace_schedule(account_id, id, ScheduleTarget::Section(section_id), week)
+        .await
+    {
+        Ok(week) => week,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("section schedule replaced");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn reorder_section_foods(
+    id: i32,
+    section_id: i32,
+    session: Session,
+    food_ids: Vec<i32>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store
+        .reorder_section_foods(account_id, id, section_id, food_ids)
+        .await
+    {
+        Ok(foods) => foods,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
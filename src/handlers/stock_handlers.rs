@@ -0,0 +1,55 @@
+use tracing::{info, instrument};
+
+use crate::{
+    store::Store,
+    types::{account::Session, stock::NewFoodStock},
+};
+
+#[instrument]
+pub async fn get_restaurant_stock(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.get_restaurant_stock(account_id, id).await {
+        Ok(stock) => stock,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn set_food_stock(
+    id: i32,
+    food_id: i32,
+    session: Session,
+    stock: NewFoodStock,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.set_food_stock(account_id, id, food_id, stock).await {
+        Ok(stock) => stock,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("food stock set");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_food_stock(
+    id: i32,
+    food_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_food_stock(account_id, id, food_id).await {
+        return Err(warp::reject::custom(e));
+    }
+    info!("food stock removed");
+    Ok(warp::reply::with_status(
+        "stock tracking stopped",
+        warp::http::StatusCode::OK,
+    ))
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+
+use crate::{
+    error::Error,
+    store::Store,
+    types::{
+        account::{Role, Session},
+        tag::NewTag,
+    },
+};
+
+#[instrument]
+pub async fn get_tags(
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let city = params.get("city").cloned();
+    let res = match store.get_tags(city).await {
+        Ok(tags) => tags,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn add_tag(
+    session: Session,
+    tag: NewTag,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.add_tag(account_id, tag).await {
+        Ok(tag) => tag,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("tag added");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn update_tag(
+    id: i32,
+    session: Session,
+    tag: NewTag,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let res = match store.update_tag(account_id, id, tag).await {
+        Ok(tag) => tag,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    info!("tag updated");
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+pub async fn delete_tag(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if let Err(e) = store.delete_tag(account_id, id).await {
+        return Err(warp::reject::custom(e));
+    }
+    Ok(warp::reply::with_status(
+        "tag deleted",
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[instrument]
+pub async fn migrate_tags(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    match store.verify_role(&account_id, Role::admin).await {
+        Ok(true) => {
+            let res = match store.migrate_free_text_tags().await {
+                Ok(res) => res,
+                Err(e) => return Err(warp::reject::custom(e)),
+            };
+            info!("free text tags migrated");
+            Ok(warp::reply::json(&res))
+        }
+        Ok(false) => Err(warp::reject::custom(Error::not_authorized)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
 
 pub fn auth_routes(
     store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
 
     let registration = warp::post()
@@ -0,0 +1,59 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        bundle_handlers::{add_bundle, delete_bundle, get_bundles, update_bundle},
+    },
+    store::Store,
+};
+
+pub fn bundle_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_bundles = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bundles"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_bundles);
+
+    let add_bundle = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bundles"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_bundle);
+
+    let update_bundle = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bundles"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_bundle);
+
+    let delete_bundle = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bundles"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_bundle);
+
+    get_bundles
+        .or(add_bundle)
+        .or(update_bundle)
+        .or(delete_bundle)
+}
@@ -0,0 +1,72 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        delivery_handlers::{
+            add_delivery_zone, check_deliverability, delete_delivery_zone, get_delivery_zones,
+            update_delivery_zone,
+        },
+    },
+    store::Store,
+};
+
+pub fn delivery_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_delivery_zones = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery-zones"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_delivery_zones);
+
+    let add_delivery_zone = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery-zones"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_delivery_zone);
+
+    let update_delivery_zone = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery-zones"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_delivery_zone);
+
+    let delete_delivery_zone = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery-zones"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_delivery_zone);
+
+    let check_deliverability = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("deliverability"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(check_deliverability);
+
+    get_delivery_zones
+        .or(add_delivery_zone)
+        .or(update_delivery_zone)
+        .or(delete_delivery_zone)
+        .or(check_deliverability)
+}
@@ -1,11 +1,21 @@
 use warp::Filter;
 
-use crate::{handlers::{authentication_handlers::auth, files_handlers::{food_image_handler, restaurant_pfp_handler}}, store::Store};
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        files_handlers::{
+            delete_restaurant_image, food_image_handler, get_restaurant_images,
+            reorder_restaurant_images, restaurant_image_handler, restaurant_pfp_handler,
+            update_restaurant_image,
+        },
+    },
+    store::Store,
+};
 
 
 pub fn file_routes(
   store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
   let store_filter = warp::any().map(move || store.clone());
 
     // static routes for serving files
@@ -31,5 +41,64 @@ pub fn file_routes(
         .and(store_filter.clone())
         .and_then(food_image_handler);
 
-        files_route.or(restaurant_pfp_upload_route).or(food_image_upload_route)
+    // gallery
+    let get_restaurant_images = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("images"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_restaurant_images);
+
+    let restaurant_image_upload_route = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("images"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(warp::multipart::form())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(restaurant_image_handler);
+
+    let reorder_restaurant_images = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("images"))
+        .and(warp::path("order"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(reorder_restaurant_images);
+
+    let update_restaurant_image = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("images"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_restaurant_image);
+
+    let delete_restaurant_image = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("images"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_restaurant_image);
+
+        files_route
+            .or(restaurant_pfp_upload_route)
+            .or(food_image_upload_route)
+            .or(get_restaurant_images)
+            .or(restaurant_image_upload_route)
+            .or(reorder_restaurant_images)
+            .or(update_restaurant_image)
+            .or(delete_restaurant_image)
 }
\ No newline at end of file
@@ -1,10 +1,13 @@
 use warp::Filter;
 
-use crate::{handlers::{authentication_handlers::auth, food_handlers::{delete_food, get_menu, post_new_food, update_food}}, store::Store};
+use crate::{handlers::{authentication_handlers::auth, food_handlers::{delete_food, get_food, get_food_options, get_food_schedule, get_menu, get_price_history, patch_food, post_new_food, replace_food_options, replace_food_schedule, search_food, update_food}, menu_handlers::{export_menu, import_menu}}, store::Store, utils::{cache::conditional, etag::if_match, merge_patch::PATCH_BODY_LIMIT}};
+
+// menu files bigger than this are rejected
+const MENU_FILE_LIMIT: u64 = 2 * 1024 * 1024;
 
 pub fn food_routes(
     store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
 
     let get_menu = warp::get()
@@ -12,10 +15,90 @@ pub fn food_routes(
     .and(warp::path::param::<i32>())
     .and(warp::path("food"))
     .and(warp::path::end())
+    .and(warp::query())
+    .and(conditional())
     .and(store_filter.clone())
     .and_then(get_menu);
 
 
+    let search_food = warp::get()
+    .and(warp::path("food"))
+    .and(warp::path("search"))
+    .and(warp::path::end())
+    .and(warp::query())
+    .and(store_filter.clone())
+    .and_then(search_food);
+
+
+    let get_food = warp::get()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path::end())
+    .and(store_filter.clone())
+    .and_then(get_food);
+
+
+    let get_food_options = warp::get()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("options"))
+    .and(warp::path::end())
+    .and(store_filter.clone())
+    .and_then(get_food_options);
+
+
+    let get_price_history = warp::get()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("price-history"))
+    .and(warp::path::end())
+    .and(store_filter.clone())
+    .and_then(get_price_history);
+
+
+    let get_food_schedule = warp::get()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("schedule"))
+    .and(warp::path::end())
+    .and(store_filter.clone())
+    .and_then(get_food_schedule);
+
+
+    let replace_food_schedule = warp::put()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("schedule"))
+    .and(warp::path::end())
+    .and(auth())
+    .and(warp::body::json())
+    .and(store_filter.clone())
+    .and_then(replace_food_schedule);
+
+
+    let replace_food_options = warp::put()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("options"))
+    .and(warp::path::end())
+    .and(auth())
+    .and(warp::body::json())
+    .and(store_filter.clone())
+    .and_then(replace_food_options);
+
+
     let post_new_food = warp::post()
     .and(warp::path("restaurants"))
     .and(auth())
@@ -31,6 +114,7 @@ pub fn food_routes(
     .and(auth())
     .and(warp::path("food"))
     .and(warp::path::end())
+    .and(if_match())
     .and(warp::body::json())
     .and(store_filter.clone())
     .and_then(update_food);
@@ -41,13 +125,64 @@ pub fn food_routes(
     .and(auth())
     .and(warp::path("food"))
     .and(warp::path::end())
+    .and(if_match())
     .and(warp::body::json())
     .and(store_filter.clone())
     .and_then(delete_food);
 
 
+    let patch_food = warp::patch()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("food"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path::end())
+    .and(auth())
+    .and(if_match())
+    .and(warp::body::content_length_limit(PATCH_BODY_LIMIT))
+    .and(warp::body::bytes())
+    .and(store_filter.clone())
+    .and_then(patch_food);
+
+
+    let import_menu = warp::post()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("menu"))
+    .and(warp::path("import"))
+    .and(warp::path::end())
+    .and(warp::query())
+    .and(auth())
+    .and(warp::body::content_length_limit(MENU_FILE_LIMIT))
+    .and(warp::body::bytes())
+    .and(store_filter.clone())
+    .and_then(import_menu);
+
+
+    let export_menu = warp::get()
+    .and(warp::path("restaurants"))
+    .and(warp::path::param::<i32>())
+    .and(warp::path("menu"))
+    .and(warp::path("export"))
+    .and(warp::path::end())
+    .and(warp::query())
+    .and(auth())
+    .and(store_filter.clone())
+    .and_then(export_menu);
+
+
     get_menu
+        .or(search_food)
+        .or(get_food)
+        .or(get_food_options)
+        .or(get_price_history)
+        .or(get_food_schedule)
+        .or(replace_food_schedule)
+        .or(replace_food_options)
         .or(post_new_food)
         .or(update_food)
         .or(delete_food)
+        .or(patch_food)
+        .or(import_menu)
+        .or(export_menu)
 }
@@ -0,0 +1,84 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        favorite_handlers::{
+            add_favorite_food, add_favorite_restaurant, delete_favorite_food,
+            delete_favorite_restaurant, get_favorites,
+        },
+        recommendation_handlers::get_recommendations,
+    },
+    store::Store,
+};
+
+pub fn me_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_favorites = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("favorites"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_favorites);
+
+    let add_favorite_restaurant = warp::post()
+        .and(warp::path("me"))
+        .and(warp::path("favorites"))
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(add_favorite_restaurant);
+
+    let delete_favorite_restaurant = warp::delete()
+        .and(warp::path("me"))
+        .and(warp::path("favorites"))
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_favorite_restaurant);
+
+    let add_favorite_food = warp::post()
+        .and(warp::path("me"))
+        .and(warp::path("favorites"))
+        .and(warp::path("foods"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(add_favorite_food);
+
+    let delete_favorite_food = warp::delete()
+        .and(warp::path("me"))
+        .and(warp::path("favorites"))
+        .and(warp::path("foods"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_favorite_food);
+
+    let get_recommendations = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("recommendations"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_recommendations);
+
+    get_favorites
+        .or(get_recommendations)
+        .or(add_favorite_restaurant)
+        .or(delete_favorite_restaurant)
+        .or(add_favorite_food)
+        .or(delete_favorite_food)
+}
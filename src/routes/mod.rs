@@ -1,7 +1,15 @@
 pub mod authentication_routes;
+pub mod bundle_routes;
 pub mod comment_routes;
+pub mod delivery_routes;
 pub mod food_routes;
+pub mod me_routes;
 pub mod file_routes;
 pub mod restaurant_routes;
+pub mod review_routes;
 pub mod order_routes;
-pub mod owner_routes;
\ No newline at end of file
+pub mod owner_routes;
+pub mod promotion_routes;
+pub mod section_routes;
+pub mod stock_routes;
+pub mod tag_routes;
\ No newline at end of file
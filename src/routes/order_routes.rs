@@ -3,14 +3,17 @@ use warp::Filter;
 use crate::{
     handlers::{
         authentication_handlers::auth,
-        order_handlers::{add_to_cart, create_cart, get_customer_orders},
+        order_handlers::{
+            add_to_cart, checkout, clear_order_delivery, create_cart, get_customer_orders,
+            get_order_status_history, remove_cart_item, set_order_delivery, set_order_status,
+        },
     },
     store::Store,
 };
 
 pub fn order_routes(
     store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
 
     let get_customer_orders = warp::get()
@@ -36,5 +39,68 @@ pub fn order_routes(
         .and(store_filter.clone())
         .and_then(add_to_cart);
 
-    get_customer_orders.or(create_cart).or(add_to_cart)
+    let remove_cart_item = warp::delete()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path("item"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(remove_cart_item);
+
+    let set_order_delivery = warp::put()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(set_order_delivery);
+
+    let clear_order_delivery = warp::delete()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path::param::<i32>())
+        .and(warp::path("delivery"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(clear_order_delivery);
+
+    let checkout = warp::post()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path("checkout"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(checkout);
+
+    let set_order_status = warp::put()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path::param::<i32>())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(set_order_status);
+
+    let get_order_status_history = warp::get()
+        .and(warp::path("order"))
+        .and(auth())
+        .and(warp::path::param::<i32>())
+        .and(warp::path("history"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_order_status_history);
+
+    get_customer_orders
+        .or(create_cart)
+        .or(checkout)
+        .or(add_to_cart)
+        .or(remove_cart_item)
+        .or(set_order_delivery)
+        .or(clear_order_delivery)
+        .or(set_order_status)
+        .or(get_order_status_history)
 }
@@ -3,14 +3,17 @@ use warp::Filter;
 use crate::{
     handlers::{
         authentication_handlers::auth,
-        owner_handlers::{create_owner, get_owner, replace_owner, update_owner_national_id},
+        owner_handlers::{
+            create_owner, get_owner, get_rating_stats, get_sales_stats, get_top_foods,
+            replace_owner, update_owner_national_id,
+        },
     },
     store::Store,
 };
 
 pub fn owner_routes(
     store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
 
     let get_owner = warp::get()
@@ -49,8 +52,47 @@ pub fn owner_routes(
         .and(store_filter.clone())
         .and_then(replace_owner);
 
+    // stats ///////////////////////////////////////////////////////////////////////////////
+    let get_sales_stats = warp::get()
+        .and(warp::path("owner"))
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_sales_stats);
+
+    let get_top_foods = warp::get()
+        .and(warp::path("owner"))
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stats"))
+        .and(warp::path("top-foods"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_top_foods);
+
+    let get_rating_stats = warp::get()
+        .and(warp::path("owner"))
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stats"))
+        .and(warp::path("ratings"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_rating_stats);
+
         get_owner
         .or(create_owner)
         .or(update_owner_national_id)
         .or(replace_owner)
+        .or(get_sales_stats)
+        .or(get_top_foods)
+        .or(get_rating_stats)
 }
@@ -0,0 +1,59 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        promotion_handlers::{add_promotion, delete_promotion, get_promotions, update_promotion},
+    },
+    store::Store,
+};
+
+pub fn promotion_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_promotions = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("promotions"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_promotions);
+
+    let add_promotion = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("promotions"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_promotion);
+
+    let update_promotion = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("promotions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_promotion);
+
+    let delete_promotion = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("promotions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_promotion);
+
+    get_promotions
+        .or(add_promotion)
+        .or(update_promotion)
+        .or(delete_promotion)
+}
@@ -1,17 +1,20 @@
-use crate::handlers::authentication_handlers::auth;
+use crate::handlers::authentication_handlers::{auth, optional_auth};
 use crate::handlers::hours_handler::{
-    delete_restaurant_hours, get_restaurant_hours, post_restaurant_hours, put_restaurant_hours,
+    delete_hours_exception, delete_restaurant_hours, get_hours_exceptions, get_open_status,
+    get_restaurant_hours, post_hours_exception, post_restaurant_hours, put_hours_exception,
+    put_restaurant_hours, replace_restaurant_hours,
 };
 use crate::handlers::restaurant_handlers::{
-    create_restaurant, delete_restaurant, get_restaurants, get_single_restaurant, search_by_city,
-    search_by_tag, update_restaurant,
+    create_restaurant, delete_restaurant, get_restaurants, get_single_restaurant, patch_restaurant,
+    search_by_city, search_by_dietary, search_by_tag, update_restaurant,
 };
 use crate::store::Store;
+use crate::utils::{cache::conditional, etag::if_match, merge_patch::PATCH_BODY_LIMIT};
 use warp::Filter;
 
 pub fn restaurant_routes(
     store: Store,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
     
     // restaurant ///////////////////////////////////////////////////////////////////////////////
@@ -19,6 +22,8 @@ pub fn restaurant_routes(
         .and(warp::path("restaurants"))
         .and(warp::path::end())
         .and(warp::query())
+        .and(optional_auth())
+        .and(conditional())
         .and(store_filter.clone())
         .and_then(get_restaurants)
         .with(warp::trace(|info| {
@@ -42,6 +47,8 @@ pub fn restaurant_routes(
         .and(warp::path("restaurants"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(optional_auth())
+        .and(conditional())
         .and(store_filter.clone())
         .and_then(get_single_restaurant);
 
@@ -50,15 +57,28 @@ pub fn restaurant_routes(
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
         .and(auth())
+        .and(if_match())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(update_restaurant);
 
+    let patch_restaurant = warp::patch()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(if_match())
+        .and(warp::body::content_length_limit(PATCH_BODY_LIMIT))
+        .and(warp::body::bytes())
+        .and(store_filter.clone())
+        .and_then(patch_restaurant);
+
     let delete_restaurant = warp::delete()
         .and(warp::path("restaurants"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
         .and(auth())
+        .and(if_match())
         .and(store_filter.clone())
         .and_then(delete_restaurant);
     // search ///////////////////////////////////////////////////////////////////////////////
@@ -67,13 +87,24 @@ pub fn restaurant_routes(
         .and(warp::path("city"))
         .and(warp::path::param::<String>())
         .and(warp::path::end())
+        .and(optional_auth())
         .and(store_filter.clone())
         .and_then(search_by_city);
 
+    let search_by_dietary = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path("dietary"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(optional_auth())
+        .and(store_filter.clone())
+        .and_then(search_by_dietary);
+
     let search_by_tag = warp::get()
         .and(warp::path("restaurants"))
         .and(warp::query())
         .and(warp::path::end())
+        .and(optional_auth())
         .and(store_filter.clone())
         .and_then(search_by_tag);
 
@@ -83,6 +114,8 @@ pub fn restaurant_routes(
         .and(warp::path("hours"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>("accept-language"))
+        .and(conditional())
         .and(store_filter.clone())
         .and_then(get_restaurant_hours);
 
@@ -100,6 +133,8 @@ pub fn restaurant_routes(
         .and(warp::path("hours"))
         .and(auth())
         .and(warp::path::end())
+        .and(if_match())
+        .and(warp::header::optional::<String>("accept-language"))
         .and(warp::body::json())
         .and(store_filter.clone())
         .and_then(put_restaurant_hours);
@@ -109,9 +144,71 @@ pub fn restaurant_routes(
         .and(warp::path("hours"))
         .and(auth())
         .and(warp::path::end())
+        .and(if_match())
         .and(warp::body::json())
         .and(store_filter.clone())
         .and_then(delete_restaurant_hours);
+
+    let replace_restaurant_hours = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("hours"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(if_match())
+        .and(warp::header::optional::<String>("accept-language"))
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(replace_restaurant_hours);
+
+    let get_open_status = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path("hours"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_open_status);
+
+    // hours exceptions ///////////////////////////////////////////////////////////////////////////////
+    let get_hours_exceptions = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path("hours"))
+        .and(warp::path("exceptions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_hours_exceptions);
+
+    let post_hours_exception = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path("hours"))
+        .and(warp::path("exceptions"))
+        .and(auth())
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(post_hours_exception);
+
+    let put_hours_exception = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path("hours"))
+        .and(warp::path("exceptions"))
+        .and(auth())
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(put_hours_exception);
+
+    let delete_hours_exception = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path("hours"))
+        .and(warp::path("exceptions"))
+        .and(auth())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(delete_hours_exception);
     /////////////////////////////////////////////////////////////////////////////////////
 
     get_restaurants
@@ -119,13 +216,22 @@ pub fn restaurant_routes(
         .or(create_restaurant)
         .or(get_single_restaurant)
         .or(update_restaurant)
+        .or(patch_restaurant)
         .or(delete_restaurant)
         // search
         .or(search_by_city)
+        .or(search_by_dietary)
         .or(search_by_tag)
         // hours
         .or(get_restaurant_hours)
         .or(post_restaurant_hours)
         .or(put_restaurant_hours)
         .or(delete_restaurant_hours)
+        .or(replace_restaurant_hours)
+        .or(get_open_status)
+        // hours exceptions
+        .or(get_hours_exceptions)
+        .or(post_hours_exception)
+        .or(put_hours_exception)
+        .or(delete_hours_exception)
 }
@@ -0,0 +1,52 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        review_handlers::{add_food_review, delete_food_review, get_food_reviews},
+    },
+    store::Store,
+};
+
+pub fn review_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_food_reviews = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("food"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reviews"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_food_reviews);
+
+    let add_food_review = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("food"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reviews"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_food_review);
+
+    let delete_food_review = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("food"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reviews"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_food_review);
+
+    get_food_reviews
+        .or(add_food_review)
+        .or(delete_food_review)
+}
@@ -0,0 +1,112 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        section_handlers::{
+            add_menu_section, delete_menu_section, get_menu_sections, get_section_schedule,
+            reorder_menu_sections, reorder_section_foods, replace_section_schedule,
+            update_menu_section,
+        },
+    },
+    store::Store,
+};
+
+pub fn section_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_menu_sections = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_menu_sections);
+
+    let add_menu_section = warp::post()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_menu_section);
+
+    let reorder_menu_sections = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path("order"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(reorder_menu_sections);
+
+    let update_menu_section = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_menu_section);
+
+    let delete_menu_section = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_menu_section);
+
+    let reorder_section_foods = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("order"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(reorder_section_foods);
+
+    let get_section_schedule = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("schedule"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(get_section_schedule);
+
+    let replace_section_schedule = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("sections"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("schedule"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(replace_section_schedule);
+
+    get_menu_sections
+        .or(add_menu_section)
+        .or(reorder_menu_sections)
+        .or(update_menu_section)
+        .or(delete_menu_section)
+        .or(reorder_section_foods)
+        .or(get_section_schedule)
+        .or(replace_section_schedule)
+}
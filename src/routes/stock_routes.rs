@@ -0,0 +1,51 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        stock_handlers::{delete_food_stock, get_restaurant_stock, set_food_stock},
+    },
+    store::Store,
+};
+
+pub fn stock_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_restaurant_stock = warp::get()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stock"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(get_restaurant_stock);
+
+    let set_food_stock = warp::put()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("food"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stock"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(set_food_stock);
+
+    let delete_food_stock = warp::delete()
+        .and(warp::path("restaurants"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("food"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("stock"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_food_stock);
+
+    get_restaurant_stock
+        .or(set_food_stock)
+        .or(delete_food_stock)
+}
@@ -0,0 +1,61 @@
+use warp::Filter;
+
+use crate::{
+    handlers::{
+        authentication_handlers::auth,
+        tag_handlers::{add_tag, delete_tag, get_tags, migrate_tags, update_tag},
+    },
+    store::Store,
+};
+
+pub fn tag_routes(
+    store: Store,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let store_filter = warp::any().map(move || store.clone());
+
+    let get_tags = warp::get()
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(get_tags);
+
+    let add_tag = warp::post()
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(add_tag);
+
+    let update_tag = warp::put()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(warp::body::json())
+        .and(store_filter.clone())
+        .and_then(update_tag);
+
+    let delete_tag = warp::delete()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(delete_tag);
+
+    let migrate_tags = warp::post()
+        .and(warp::path("tags"))
+        .and(warp::path("migrate"))
+        .and(warp::path::end())
+        .and(auth())
+        .and(store_filter.clone())
+        .and_then(migrate_tags);
+
+    get_tags
+        .or(add_tag)
+        .or(update_tag)
+        .or(delete_tag)
+        .or(migrate_tags)
+}
@@ -0,0 +1,215 @@
+use chrono::{Local, NaiveDateTime, NaiveTime};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::{
+        availability::{daily_window, ScheduleTarget, Schedules},
+        food::Food,
+        restaurant::{next_time_of_day, validate_week, HoursInterval},
+    },
+};
+
+use super::Store;
+
+fn map_interval(row: &PgRow) -> HoursInterval {
+    HoursInterval {
+        day_of_week: row.get("day_of_week"),
+        open_time: row.get("open_time"),
+        close_time: row.get("close_time"),
+    }
+}
+
+// the windows of the given foods and sections
+// a section without windows falls back to its `available_from`/`available_to`, every day
+async fn load_schedules(
+    connection: &mut PgConnection,
+    food_ids: &[i32],
+    section_ids: &[i32],
+) -> Result<Schedules, Error> {
+    let mut schedules = Schedules::default();
+    let rows = sqlx::query(
+        "SELECT * FROM availability_window
+        WHERE food_id = ANY($1) OR section_id = ANY($2);",
+    )
+    .bind(food_ids)
+    .bind(section_ids)
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+    for row in rows {
+        let interval = map_interval(&row);
+        match (row.get::<Option<i32>, _>("food_id"), row.get::<Option<i32>, _>("section_id")) {
+            (Some(food_id), _) => schedules.foods.entry(food_id).or_default().push(interval),
+            (_, Some(section_id)) => schedules.sections.entry(section_id).or_default().push(interval),
+            _ => {}
+        }
+    }
+
+    let sections = sqlx::query(
+        "SELECT id, available_from, available_to FROM menu_section
+        WHERE id = ANY($1) AND available_from IS NOT NULL AND available_to IS NOT NULL;",
+    )
+    .bind(section_ids)
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+    for row in sections {
+        schedules
+            .sections
+            .entry(row.get("id"))
+            .or_insert_with(|| daily_window(row.get("available_from"), row.get("available_to")));
+    }
+    Ok(schedules)
+}
+
+// narrows `orderable_now` of foods from any restaurants to their windows at server time
+// on a connection of its own, or on a transaction's (the cart reads the windows with the foods)
+pub(super) async fn apply_schedules_on(connection: &mut PgConnection, foods: &mut [Food]) -> Result<(), Error> {
+    let now = Local::now().naive_local();
+    let food_ids: Vec<i32> = foods.iter().map(|food| food.id).collect();
+    let mut section_ids: Vec<i32> = foods.iter().filter_map(|food| food.section_id).collect();
+    section_ids.sort_unstable();
+    section_ids.dedup();
+
+    let schedules = load_schedules(connection, &food_ids, &section_ids).await?;
+    for food in foods {
+        food.orderable_now = schedules.is_orderable_at(food, now);
+    }
+    Ok(())
+}
+
+impl Store {
+    pub async fn apply_schedules(&self, foods: &mut [Food]) -> Result<(), Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        apply_schedules_on(&mut connection, foods).await
+    }
+
+    // the next time a window of the restaurant's foods or sections opens or closes, on any day,
+    // so a cached `orderable_now` doesn't outlive it
+    pub async fn next_schedule_change(
+        &self,
+        restaurant_id: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<NaiveDateTime>, Error> {
+        let bounds = sqlx::query(
+            "SELECT open_time, close_time FROM availability_window
+            WHERE food_id IN (SELECT id FROM food WHERE restaurant_id = $1)
+            OR section_id IN (SELECT id FROM menu_section WHERE restaurant_id = $1)
+            UNION
+            SELECT available_from, available_to FROM menu_section
+            WHERE restaurant_id = $1 AND available_from IS NOT NULL AND available_to IS NOT NULL;",
+        )
+        .bind(restaurant_id)
+        .map(|row: PgRow| (row.get::<NaiveTime, _>(0), row.get::<NaiveTime, _>(1)))
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+        Ok(bounds
+            .into_iter()
+            .flat_map(|(open_time, close_time)| [open_time, close_time])
+            .map(|time| next_time_of_day(now, time))
+            .min())
+    }
+
+    // the food (or section) has to belong to the restaurant, a 404 otherwise
+    async fn check_schedule_target(
+        &self,
+        restaurant_id: i32,
+        target: ScheduleTarget,
+    ) -> Result<(), Error> {
+        let (query, id) = match target {
+            ScheduleTarget::Food(id) => ("SELECT EXISTS (SELECT 1 FROM food WHERE id = $1 AND restaurant_id = $2);", id),
+            ScheduleTarget::Section(id) => (
+                "SELECT EXISTS (SELECT 1 FROM menu_section WHERE id = $1 AND restaurant_id = $2);",
+                id,
+            ),
+        };
+        let exists = sqlx::query_scalar::<_, bool>(query)
+            .bind(id)
+            .bind(restaurant_id)
+            .fetch_one(&self.connection)
+            .await
+            .map_err(Error::database_query_error)?;
+        if exists {
+            Ok(())
+        } else {
+            Err(Error::database_query_error(sqlx::Error::RowNotFound))
+        }
+    }
+
+    pub async fn get_schedule(
+        &self,
+        restaurant_id: i32,
+        target: ScheduleTarget,
+    ) -> Result<Vec<HoursInterval>, Error> {
+        self.check_schedule_target(restaurant_id, target).await?;
+        let (food_id, section_id) = match target {
+            ScheduleTarget::Food(id) => (Some(id), None),
+            ScheduleTarget::Section(id) => (None, Some(id)),
+        };
+        match sqlx::query(
+            "SELECT * FROM availability_window
+            WHERE food_id = $1 OR section_id = $2
+            ORDER BY day_of_week, open_time;",
+        )
+        .bind(food_id)
+        .bind(section_id)
+        .map(|row: PgRow| map_interval(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(windows) => Ok(windows),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // replaces every window of the food or section, an empty week makes it orderable any time
+    pub async fn replace_schedule(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        target: ScheduleTarget,
+        week: Vec<HoursInterval>,
+    ) -> Result<Vec<HoursInterval>, Error> {
+        validate_week(&week)?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        self.check_schedule_target(restaurant_id, target).await?;
+        let (food_id, section_id) = match target {
+            ScheduleTarget::Food(id) => (Some(id), None),
+            ScheduleTarget::Section(id) => (None, Some(id)),
+        };
+
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        sqlx::query("DELETE FROM availability_window WHERE food_id = $1 OR section_id = $2;")
+            .bind(food_id)
+            .bind(section_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        for interval in &week {
+            sqlx::query(
+                "INSERT INTO availability_window (food_id, section_id, day_of_week, open_time, close_time)
+                VALUES ($1, $2, $3, $4, $5);",
+            )
+            .bind(food_id)
+            .bind(section_id)
+            .bind(&interval.day_of_week)
+            .bind(interval.open_time)
+            .bind(interval.close_time)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        }
+        tx.commit().await.map_err(Error::database_query_error)?;
+
+        self.cache.invalidate_menu(restaurant_id);
+        self.get_schedule(restaurant_id, target).await
+    }
+}
@@ -0,0 +1,346 @@
+use sqlx::{postgres::PgRow, PgConnection, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::bundle::{Bundle, BundleChoice, BundleItem, BundleSlot, NewBundle},
+};
+
+use super::{section::check_section, Store};
+
+fn map_bundle(row: PgRow) -> Bundle {
+    Bundle {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        section_id: row.get("section_id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        image: row.get("image"),
+        price: row.get("price"),
+        available: row.get("available"),
+        sort_order: row.get("sort_order"),
+        items: Vec::new(),
+        slots: Vec::new(),
+        orderable: false,
+    }
+}
+
+// the restaurant's bundles (or just one of them) with their items and slots, in display order
+async fn load_bundles(
+    connection: &mut PgConnection,
+    restaurant_id: i32,
+    bundle_id: Option<i32>,
+) -> Result<Vec<Bundle>, Error> {
+    let mut bundles = sqlx::query(
+        "SELECT * FROM bundle
+        WHERE restaurant_id = $1 AND ($2::INT IS NULL OR id = $2)
+        ORDER BY sort_order, id;",
+    )
+    .bind(restaurant_id)
+    .bind(bundle_id)
+    .map(map_bundle)
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+    let ids: Vec<i32> = bundles.iter().map(|bundle| bundle.id).collect();
+
+    let items = sqlx::query(
+        "SELECT bundle_item.bundle_id, bundle_item.food_id, bundle_item.quantity, food.name, food.available
+        FROM bundle_item
+        JOIN food ON food.id = bundle_item.food_id
+        WHERE bundle_item.bundle_id = ANY($1)
+        ORDER BY bundle_item.id;",
+    )
+    .bind(&ids)
+    .map(|row: PgRow| {
+        (
+            row.get::<i32, _>("bundle_id"),
+            BundleItem {
+                food_id: row.get("food_id"),
+                name: row.get("name"),
+                quantity: row.get("quantity"),
+                available: row.get("available"),
+            },
+        )
+    })
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    let slots = sqlx::query(
+        "SELECT * FROM bundle_slot
+        WHERE bundle_id = ANY($1)
+        ORDER BY sort_order, id;",
+    )
+    .bind(&ids)
+    .map(|row: PgRow| {
+        (
+            row.get::<i32, _>("bundle_id"),
+            BundleSlot {
+                id: row.get("id"),
+                name: row.get("name"),
+                quantity: row.get("quantity"),
+                choices: Vec::new(),
+            },
+        )
+    })
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    let choices = sqlx::query(
+        "SELECT bundle_slot_choice.slot_id, bundle_slot_choice.food_id, food.name, food.available
+        FROM bundle_slot_choice
+        JOIN bundle_slot ON bundle_slot.id = bundle_slot_choice.slot_id
+        JOIN food ON food.id = bundle_slot_choice.food_id
+        WHERE bundle_slot.bundle_id = ANY($1)
+        ORDER BY bundle_slot_choice.sort_order;",
+    )
+    .bind(&ids)
+    .map(|row: PgRow| {
+        (
+            row.get::<i32, _>("slot_id"),
+            BundleChoice {
+                food_id: row.get("food_id"),
+                name: row.get("name"),
+                available: row.get("available"),
+            },
+        )
+    })
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    for (bundle_id, item) in items {
+        if let Some(bundle) = bundles.iter_mut().find(|bundle| bundle.id == bundle_id) {
+            bundle.items.push(item);
+        }
+    }
+    for (bundle_id, mut slot) in slots {
+        slot.choices = choices
+            .iter()
+            .filter(|(slot_id, _)| *slot_id == slot.id)
+            .map(|(_, choice)| choice.clone())
+            .collect();
+        if let Some(bundle) = bundles.iter_mut().find(|bundle| bundle.id == bundle_id) {
+            bundle.slots.push(slot);
+        }
+    }
+    for bundle in &mut bundles {
+        bundle.orderable = bundle.is_orderable();
+    }
+    Ok(bundles)
+}
+
+// writes the items and slots of a bundle that has none
+// on a connection of its own, or on a transaction's (the cart reads the bundle it adds)
+pub(super) async fn load_bundle(
+    connection: &mut PgConnection,
+    restaurant_id: i32,
+    bundle_id: i32,
+) -> Result<Bundle, Error> {
+    load_bundles(connection, restaurant_id, Some(bundle_id))
+        .await?
+        .pop()
+        .ok_or(Error::database_query_error(sqlx::Error::RowNotFound))
+}
+
+async fn insert_components(
+    tx: &mut Transaction<'_, Postgres>,
+    bundle_id: i32,
+    bundle: &NewBundle,
+) -> Result<(), Error> {
+    for item in &bundle.items {
+        sqlx::query("INSERT INTO bundle_item (bundle_id, food_id, quantity) VALUES ($1, $2, $3);")
+            .bind(bundle_id)
+            .bind(item.food_id)
+            .bind(item.quantity)
+            .execute(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+    }
+    for (slot_order, slot) in bundle.slots.iter().enumerate() {
+        let slot_id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO bundle_slot (bundle_id, name, quantity, sort_order)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id;",
+        )
+        .bind(bundle_id)
+        .bind(slot.name.trim())
+        .bind(slot.quantity)
+        .bind(slot_order as i32)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        for (choice_order, food_id) in slot.choices.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO bundle_slot_choice (slot_id, food_id, sort_order) VALUES ($1, $2, $3);",
+            )
+            .bind(slot_id)
+            .bind(food_id)
+            .bind(choice_order as i32)
+            .execute(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        }
+    }
+    Ok(())
+}
+
+impl Store {
+    pub async fn get_bundles(&self, restaurant_id: i32) -> Result<Vec<Bundle>, Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        load_bundles(&mut connection, restaurant_id, None).await
+    }
+
+    pub async fn get_bundle(&self, restaurant_id: i32, bundle_id: i32) -> Result<Bundle, Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        load_bundle(&mut connection, restaurant_id, bundle_id).await
+    }
+
+    // every food of the bundle has to be on this restaurant's menu, and so does its section
+    async fn check_bundle(&self, restaurant_id: i32, bundle: &NewBundle) -> Result<(), Error> {
+        let food_ids = bundle.food_ids();
+        let found = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM food WHERE id = ANY($1) AND restaurant_id = $2;",
+        )
+        .bind(&food_ids)
+        .bind(restaurant_id)
+        .fetch_one(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+        if found != food_ids.len() as i64 {
+            return Err(Error::invalid_field(format!(
+                "every food of the bundle has to belong to restaurant {restaurant_id}"
+            )));
+        }
+        check_section(&self.connection, restaurant_id, bundle.section_id).await
+    }
+
+    pub async fn add_bundle(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        bundle: NewBundle,
+    ) -> Result<Bundle, Error> {
+        bundle.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        self.check_bundle(restaurant_id, &bundle).await?;
+
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let bundle_id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO bundle (restaurant_id, section_id, name, description, image, price, available, sort_order)
+            VALUES ($1, $2, $3, $4, $5, $6, $7,
+              (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM bundle WHERE restaurant_id = $1))
+            RETURNING id;",
+        )
+        .bind(restaurant_id)
+        .bind(bundle.section_id)
+        .bind(bundle.name.trim())
+        .bind(&bundle.description)
+        .bind(&bundle.image)
+        .bind(bundle.price)
+        .bind(bundle.available)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        insert_components(&mut tx, bundle_id, &bundle).await?;
+        tx.commit().await.map_err(Error::database_query_error)?;
+
+        self.cache.invalidate_menu(restaurant_id);
+        self.get_bundle(restaurant_id, bundle_id).await
+    }
+
+    // replaces the whole bundle, bundles already in carts keep the foods they were added with
+    pub async fn update_bundle(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        bundle_id: i32,
+        bundle: NewBundle,
+    ) -> Result<Bundle, Error> {
+        bundle.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        self.check_bundle(restaurant_id, &bundle).await?;
+
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        sqlx::query(
+            "UPDATE bundle
+            SET section_id = $1, name = $2, description = $3, image = $4, price = $5, available = $6
+            WHERE id = $7 AND restaurant_id = $8
+            RETURNING id;",
+        )
+        .bind(bundle.section_id)
+        .bind(bundle.name.trim())
+        .bind(&bundle.description)
+        .bind(&bundle.image)
+        .bind(bundle.price)
+        .bind(bundle.available)
+        .bind(bundle_id)
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        for table in ["bundle_item", "bundle_slot"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE bundle_id = $1;"))
+                .bind(bundle_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+        }
+        insert_components(&mut tx, bundle_id, &bundle).await?;
+        tx.commit().await.map_err(Error::database_query_error)?;
+
+        self.cache.invalidate_menu(restaurant_id);
+        self.get_bundle(restaurant_id, bundle_id).await
+    }
+
+    // carts holding the bundle lose it, placed orders keep it as it was
+    pub async fn delete_bundle(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        bundle_id: i32,
+    ) -> Result<bool, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        sqlx::query(
+            "DELETE FROM item
+            USING orders, bundle
+            WHERE item.bundle_id = $1 AND bundle.id = item.bundle_id AND bundle.restaurant_id = $2
+              AND orders.id = item.order_id AND orders.status = 'cart';",
+        )
+        .bind(bundle_id)
+        .bind(restaurant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        let res = sqlx::query("DELETE FROM bundle WHERE id = $1 AND restaurant_id = $2;")
+            .bind(bundle_id)
+            .bind(restaurant_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        tx.commit().await.map_err(Error::database_query_error)?;
+
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -83,7 +83,11 @@ impl Store {
         .fetch_one(&self.connection)
         .await
         {
-            Ok(comments) => Ok(comments),
+            Ok(comment) => {
+                // the restaurant and the lists show the average rating
+                self.cache.invalidate_restaurant(restaurant_id);
+                Ok(comment)
+            }
             Err(e) => Err(Error::database_query_error(e)),
         }
     }
@@ -94,14 +98,20 @@ impl Store {
             WHERE id = $1
             AND (account_id = $2 OR EXISTS (
             SELECT 1 FROM account WHERE id = $2 AND role = 'admin'
-            ));",
+            ))
+            RETURNING restaurant_id;",
         )
         .bind(comment_id)
         .bind(account_id)
-        .execute(&self.connection)
+        .fetch_optional(&self.connection)
         .await
         {
-            Ok(_) => Ok(true),
+            Ok(restaurant_id) => {
+                if let Some(row) = restaurant_id {
+                    self.cache.invalidate_restaurant(row.get("restaurant_id"));
+                }
+                Ok(true)
+            }
             Err(e) => Err(Error::database_query_error(e)),
         }
     }
@@ -0,0 +1,206 @@
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::delivery::{
+        distance_km, pick_zone, Deliverability, DeliveryCheck, DeliveryZone, NewDeliveryZone,
+        ZoneArea,
+    },
+};
+
+use super::Store;
+
+fn map_delivery_zone(row: PgRow) -> DeliveryZone {
+    DeliveryZone {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        name: row.get("name"),
+        area: ZoneArea::from_columns(row.get("radius_km"), row.get("polygon")),
+        fee: row.get("fee"),
+        minimum_order: row.get("minimum_order"),
+        estimated_minutes: row.get("estimated_minutes"),
+    }
+}
+
+// cheapest first, `pick_zone` takes the first that covers the location
+async fn zones_of(executor: impl PgExecutor<'_>, restaurant_id: i32) -> Result<Vec<DeliveryZone>, Error> {
+    sqlx::query(
+        "SELECT * FROM delivery_zone
+        WHERE restaurant_id = $1
+        ORDER BY fee, id;",
+    )
+    .bind(restaurant_id)
+    .map(map_delivery_zone)
+    .fetch_all(executor)
+    .await
+    .map_err(Error::database_query_error)
+}
+
+// on a connection of its own, or on a transaction's (checkout checks the cart it's about to close)
+pub(super) async fn deliverability(
+    connection: &mut PgConnection,
+    restaurant_id: i32,
+    check: DeliveryCheck,
+) -> Result<Deliverability, Error> {
+    let restaurant_location: [f64; 2] = match sqlx::query(
+        "SELECT location FROM restaurant
+        WHERE id = $1;",
+    )
+    .bind(restaurant_id)
+    .map(|row: PgRow| row.get("location"))
+    .fetch_one(&mut *connection)
+    .await
+    {
+        Ok(location) => location,
+        Err(e) => return Err(Error::database_query_error(e)),
+    };
+
+    let zones = zones_of(&mut *connection, restaurant_id).await?;
+    let distance_km = distance_km(restaurant_location, check.location);
+
+    Ok(match pick_zone(&zones, restaurant_location, check.location) {
+        Some(zone) => Deliverability {
+            deliverable: true,
+            distance_km,
+            zone_id: Some(zone.id),
+            fee: Some(zone.fee),
+            minimum_order: Some(zone.minimum_order),
+            estimated_minutes: Some(zone.estimated_minutes),
+            meets_minimum: check.subtotal.map(|subtotal| subtotal >= zone.minimum_order),
+        },
+        None => Deliverability {
+            deliverable: false,
+            distance_km,
+            zone_id: None,
+            fee: None,
+            minimum_order: None,
+            estimated_minutes: None,
+            meets_minimum: None,
+        },
+    })
+}
+
+impl Store {
+    pub async fn get_delivery_zones(&self, restaurant_id: i32) -> Result<Vec<DeliveryZone>, Error> {
+        zones_of(&self.connection, restaurant_id).await
+    }
+
+    pub async fn add_delivery_zone(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        zone: NewDeliveryZone,
+    ) -> Result<DeliveryZone, Error> {
+        zone.area.validate()?;
+        match self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                let (radius_km, polygon) = zone.area.to_columns();
+                match sqlx::query(
+                    "INSERT INTO delivery_zone
+                    (restaurant_id, name, radius_km, polygon, fee, minimum_order, estimated_minutes)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING *;",
+                )
+                .bind(restaurant_id)
+                .bind(zone.name)
+                .bind(radius_km)
+                .bind(polygon)
+                .bind(zone.fee)
+                .bind(zone.minimum_order)
+                .bind(zone.estimated_minutes)
+                .map(map_delivery_zone)
+                .fetch_one(&self.connection)
+                .await
+                {
+                    Ok(zone) => Ok(zone),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn update_delivery_zone(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        zone_id: i32,
+        zone: NewDeliveryZone,
+    ) -> Result<DeliveryZone, Error> {
+        zone.area.validate()?;
+        match self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                let (radius_km, polygon) = zone.area.to_columns();
+                match sqlx::query(
+                    "UPDATE delivery_zone
+                    SET name = $1, radius_km = $2, polygon = $3, fee = $4, minimum_order = $5, estimated_minutes = $6
+                    WHERE id = $7 AND restaurant_id = $8
+                    RETURNING *;",
+                )
+                .bind(zone.name)
+                .bind(radius_km)
+                .bind(polygon)
+                .bind(zone.fee)
+                .bind(zone.minimum_order)
+                .bind(zone.estimated_minutes)
+                .bind(zone_id)
+                .bind(restaurant_id)
+                .map(map_delivery_zone)
+                .fetch_one(&self.connection)
+                .await
+                {
+                    Ok(zone) => Ok(zone),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn delete_delivery_zone(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        zone_id: i32,
+    ) -> Result<bool, Error> {
+        match self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                match sqlx::query(
+                    "DELETE FROM delivery_zone
+                    WHERE id = $1 AND restaurant_id = $2;",
+                )
+                .bind(zone_id)
+                .bind(restaurant_id)
+                .execute(&self.connection)
+                .await
+                {
+                    Ok(_) => Ok(true),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn check_deliverability(
+        &self,
+        restaurant_id: i32,
+        check: DeliveryCheck,
+    ) -> Result<Deliverability, Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        deliverability(&mut connection, restaurant_id, check).await
+    }
+}
@@ -0,0 +1,147 @@
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::{food::Food, restaurant::Restaurant},
+};
+
+use super::{food::map_food, restaurant::map_restaurant, Store};
+
+impl Store {
+    // adding something twice is not an error
+    pub async fn add_favorite_restaurant(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "INSERT INTO favorite_restaurant (account_id, restaurant_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING",
+        )
+        .bind(account_id)
+        .bind(restaurant_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn delete_favorite_restaurant(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "DELETE FROM favorite_restaurant
+            WHERE account_id = $1 AND restaurant_id = $2",
+        )
+        .bind(account_id)
+        .bind(restaurant_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn add_favorite_food(&self, account_id: Uuid, food_id: i32) -> Result<bool, Error> {
+        match sqlx::query(
+            "INSERT INTO favorite_food (account_id, food_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING",
+        )
+        .bind(account_id)
+        .bind(food_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn delete_favorite_food(
+        &self,
+        account_id: Uuid,
+        food_id: i32,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "DELETE FROM favorite_food
+            WHERE account_id = $1 AND food_id = $2",
+        )
+        .bind(account_id)
+        .bind(food_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // newest first
+    pub async fn get_favorite_restaurants(
+        &self,
+        account_id: Uuid,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Restaurant>, Error> {
+        match sqlx::query(
+            "SELECT
+            restaurant.id, restaurant.name, restaurant.distance, restaurant.tags, restaurant.image,
+            restaurant.address, restaurant.city, restaurant.location, restaurant.version,
+            COALESCE((SELECT AVG(comments.rating) FROM comments WHERE comments.restaurant_id = restaurant.id), 0) AS rating,
+            TRUE AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
+            FROM favorite_restaurant
+            JOIN restaurant ON restaurant.id = favorite_restaurant.restaurant_id
+            WHERE favorite_restaurant.account_id = $1
+            ORDER BY favorite_restaurant.created_on DESC, restaurant.id
+            LIMIT $2 OFFSET $3",
+        )
+        .bind(account_id)
+        .bind(limit)
+        .bind(offset)
+        .map(map_restaurant)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(restaurants) => Ok(restaurants),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn get_favorite_foods(
+        &self,
+        account_id: Uuid,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Food>, Error> {
+        match sqlx::query(
+            "SELECT food.*
+            FROM favorite_food
+            JOIN food ON food.id = favorite_food.food_id
+            WHERE favorite_food.account_id = $1
+            ORDER BY favorite_food.created_on DESC, food.id
+            LIMIT $2 OFFSET $3",
+        )
+        .bind(account_id)
+        .bind(limit)
+        .bind(offset)
+        .map(map_food)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(mut foods) => {
+                self.apply_promotions(&mut foods).await?;
+                self.apply_schedules(&mut foods).await?;
+                Ok(foods)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+}
@@ -1,72 +1,126 @@
 use crate::{
     error::Error,
-    types::{account::Role, food::{Food, NewFood}}, verify_roles,
+    types::{account::Role, dietary::{normalize_diets, validate_labels}, food::{Food, NewFood}}, verify_roles,
 };
-use sqlx::Row;
+use sqlx::{postgres::PgRow, Row};
 use uuid::Uuid;
 
-use super::Store;
+use crate::utils::etag::IfMatch;
+
+use super::{price_history::record_price, section::check_section, version::check_version, Store};
+
+// `effective_price` starts at `price`, `Store::apply_promotions` fills in the rest
+// `orderable_now` starts at `available`, `Store::apply_schedules` narrows it to the food's windows
+pub(crate) fn map_food(row: PgRow) -> Food {
+    Food {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        name: row.get("name"),
+        image: row.get("image"),
+        tag: row.get("tag"),
+        tag_id: row.get("tag_id"),
+        price: row.get("price"),
+        ingredient: row.get("ingredient"),
+        available: row.get("available"),
+        allergens: row.get("allergens"),
+        diets: row.get("diets"),
+        spicy_level: row.get("spicy_level"),
+        rating: row.get("rating"),
+        rating_count: row.get("rating_count"),
+        section_id: row.get("section_id"),
+        sort_order: row.get("sort_order"),
+        version: row.get("version"),
+        effective_price: row.get("price"),
+        promotion_id: None,
+        orderable_now: row.get("available"),
+    }
+}
 
 impl Store {
     pub async fn get_menu(&self, restaurant_id: i32) -> Result<Vec<Food>, Error> {
         match sqlx::query(
             "SELECT * from Food
-          WHERE restaurant_id = $1",
+          WHERE restaurant_id = $1
+          ORDER BY sort_order, id",
         )
         .bind(restaurant_id)
-        .map(|row| Food {
-            id: row.get("id"),
-            restaurant_id: row.get("restaurant_id"),
-            name: row.get("name"),
-            image: row.get("image"),
-            tag: row.get("tag"),
-            price: row.get("price"),
-            discount: row.get("discount"),
-            discount_price: row.get("discount_price"),
-            ingredient: row.get("ingredient"),
-            available: row.get("available"),
-        })
+        .map(map_food)
         .fetch_all(&self.connection)
         .await
         {
-            Ok(foods) => Ok(foods),
+            Ok(mut foods) => {
+                self.apply_promotions(&mut foods).await?;
+                self.apply_schedules(&mut foods).await?;
+                Ok(foods)
+            }
             Err(e) => Err(Error::database_query_error(e)),
         }
     }
 
-    pub async fn post_new_food(&self, food: NewFood, account_id: Uuid) -> Result<Food, Error> {
+    pub async fn get_food(&self, restaurant_id: i32, food_id: i32) -> Result<Food, Error> {
+        match sqlx::query(
+            "SELECT * from food
+          WHERE restaurant_id = $1 AND id = $2",
+        )
+        .bind(restaurant_id)
+        .bind(food_id)
+        .map(map_food)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(mut food) => {
+                self.apply_promotions(std::slice::from_mut(&mut food)).await?;
+                self.apply_schedules(std::slice::from_mut(&mut food)).await?;
+                Ok(food)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn post_new_food(&self, mut food: NewFood, account_id: Uuid) -> Result<Food, Error> {
+        normalize_diets(&mut food.diets);
+        validate_labels(food.allergens.as_deref().unwrap_or_default(), &food.diets, food.spicy_level)?;
         match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
           Ok(true) => {
+            check_section(&self.connection, food.restaurant_id, food.section_id).await?;
+            let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+            // new foods go to the end of their section
             match sqlx::query(
-              "INSERT INTO food (restaurant_id, name, image, tag, price, discount, discount_price, ingredient, available)
-              VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+              "INSERT INTO food (
+                restaurant_id, name, image, tag, tag_id, price, ingredient, available, section_id, sort_order,
+                allergens, diets, spicy_level
+              )
+              VALUES ($1, $2, $3, COALESCE((SELECT slug FROM tag WHERE id = $5), $4), $5, $6, $7, $8, $9, (
+                SELECT COALESCE(MAX(sort_order) + 1, 0) FROM food
+                WHERE restaurant_id = $1 AND section_id IS NOT DISTINCT FROM $9
+              ), $10, $11, $12)
               RETURNING *;"
             )
             .bind(food.restaurant_id) 
             .bind(food.name) 
             .bind(food.image) 
             .bind(food.tag) 
+            .bind(food.tag_id) 
             .bind(food.price) 
-            .bind(food.discount) 
-            .bind(food.discount_price) 
             .bind(food.ingredient) 
             .bind(food.available)
-            .map(|row| Food {
-              id: row.get("id"),
-              restaurant_id: row.get("restaurant_id"),
-              name: row.get("name"),
-              image: row.get("image"),
-              tag: row.get("tag"),
-              price: row.get("price"),
-              discount: row.get("discount"),
-              discount_price: row.get("discount_price"),
-              ingredient: row.get("ingredient"),
-              available: row.get("available"),
-          })
-          .fetch_one(&self.connection)
+            .bind(food.section_id)
+            .bind(food.allergens)
+            .bind(food.diets)
+            .bind(food.spicy_level)
+            .map(map_food)
+          .fetch_one(&mut *tx)
           .await
             {
-              Ok(food) => Ok(food),
+              Ok(mut food) => {
+                record_price(&mut *tx, food.id).await?;
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_menu(food.restaurant_id);
+                self.apply_promotions(std::slice::from_mut(&mut food)).await?;
+                self.apply_schedules(std::slice::from_mut(&mut food)).await?;
+                Ok(food)
+              }
               Err(e) => Err(Error::database_query_error(e)),
             }
           }
@@ -75,41 +129,55 @@ impl Store {
         }
     }
 
-    pub async fn update_food(&self, food: Food, account_id: Uuid) -> Result<Food, Error> {
+    pub async fn update_food(&self, mut food: Food, account_id: Uuid, if_match: &IfMatch) -> Result<Food, Error> {
+        normalize_diets(&mut food.diets);
+        validate_labels(food.allergens.as_deref().unwrap_or_default(), &food.diets, food.spicy_level)?;
         match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
           Ok(true) => {
+            let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+            check_version(&mut tx, "food", food.id, if_match).await?;
+            check_section(&mut *tx, food.restaurant_id, food.section_id).await?;
+
+            // moving to another section puts the food at its end
             match sqlx::query(
               "UPDATE food
-              SET name = $1, image = $2, tag = $3, price = $4, discount = $5, discount_price = $6, ingredient = $7, available = $8
-              WHERE id = $9 AND restaurant_id = $10
+              SET name = $1, image = $2, tag = COALESCE((SELECT slug FROM tag WHERE id = $9), $3), tag_id = $9,
+              price = $4, ingredient = $5, available = $6,
+              sort_order = CASE WHEN section_id IS NOT DISTINCT FROM $10 THEN sort_order ELSE (
+                SELECT COALESCE(MAX(other.sort_order) + 1, 0) FROM food other
+                WHERE other.restaurant_id = $8 AND other.section_id IS NOT DISTINCT FROM $10
+              ) END,
+              section_id = $10,
+              allergens = $11, diets = $12, spicy_level = $13,
+              version = version + 1
+              WHERE id = $7 AND restaurant_id = $8
               RETURNING *;"
             )
             .bind(food.name) 
             .bind(food.image) 
             .bind(food.tag) 
             .bind(food.price) 
-            .bind(food.discount) 
-            .bind(food.discount_price) 
             .bind(food.ingredient) 
             .bind(food.available)
             .bind(food.id)
             .bind(food.restaurant_id)
-            .map(|row| Food {
-              id: row.get("id"),
-              restaurant_id: row.get("restaurant_id"),
-              name: row.get("name"),
-              image: row.get("image"),
-              tag: row.get("tag"),
-              price: row.get("price"),
-              discount: row.get("discount"),
-              discount_price: row.get("discount_price"),
-              ingredient: row.get("ingredient"),
-              available: row.get("available"),
-          })
-          .fetch_one(&self.connection)
+            .bind(food.tag_id)
+            .bind(food.section_id)
+            .bind(food.allergens)
+            .bind(food.diets)
+            .bind(food.spicy_level)
+            .map(map_food)
+          .fetch_one(&mut *tx)
           .await
             {
-              Ok(food) => Ok(food),
+              Ok(mut food) => {
+                record_price(&mut *tx, food.id).await?;
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_menu(food.restaurant_id);
+                self.apply_promotions(std::slice::from_mut(&mut food)).await?;
+                self.apply_schedules(std::slice::from_mut(&mut food)).await?;
+                Ok(food)
+              }
               Err(e) => Err(Error::database_query_error(e)),
             }
           }
@@ -118,19 +186,26 @@ impl Store {
         }
     }
 
-    pub async fn delete_food(&self, food: Food, account_id: Uuid) -> Result<bool, Error> {
+    pub async fn delete_food(&self, food: Food, account_id: Uuid, if_match: &IfMatch) -> Result<bool, Error> {
         match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
           Ok(true) => {
+            let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+            check_version(&mut tx, "food", food.id, if_match).await?;
+
             match sqlx::query(
               "DELETE FROM food
               WHERE id = $1 AND restaurant_id = $2;"
             )
             .bind(food.id)
             .bind(food.restaurant_id)
-            .execute(&self.connection)
+            .execute(&mut *tx)
             .await 
             {
-              Ok(_) => Ok(true),
+              Ok(_) => {
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_menu(food.restaurant_id);
+                Ok(true)
+              }
               Err(e) => Err(Error::database_query_error(e)),
             }
           }
@@ -147,7 +222,7 @@ impl Store {
   ) -> Result<bool, Error> {
       match sqlx::query(
           "UPDATE food 
-          SET image = $1
+          SET image = $1, version = version + 1
           WHERE id = $2 AND restaurant_id = $3",
       )
       .bind(url)
@@ -156,7 +231,10 @@ impl Store {
       .execute(&self.connection)
       .await
       {
-          Ok(_) => Ok(true),
+          Ok(_) => {
+              self.cache.invalidate_menu(restaurant_id);
+              Ok(true)
+          }
           Err(e) => Err(Error::database_query_error(e)),
       }
   }
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::food_option::{FoodOption, NewOptionGroup, OptionGroup},
+};
+
+use super::Store;
+
+fn map_option_group(row: PgRow) -> OptionGroup {
+    OptionGroup {
+        id: row.get("id"),
+        food_id: row.get("food_id"),
+        name: row.get("name"),
+        min_select: row.get("min_select"),
+        max_select: row.get("max_select"),
+        sort_order: row.get("sort_order"),
+        options: Vec::new(),
+    }
+}
+
+fn map_food_option(row: PgRow) -> FoodOption {
+    FoodOption {
+        id: row.get("id"),
+        group_id: row.get("group_id"),
+        name: row.get("name"),
+        price_delta: row.get("price_delta"),
+        available: row.get("available"),
+        sort_order: row.get("sort_order"),
+    }
+}
+
+// groups (and their options) of the given foods, keyed by food id, in display order
+// on a connection of its own, or on a transaction's (adding to the cart reads them with the food)
+pub(super) async fn load_option_groups(
+    connection: &mut PgConnection,
+    food_ids: &[i32],
+) -> Result<HashMap<i32, Vec<OptionGroup>>, Error> {
+    let mut groups = sqlx::query(
+        "SELECT * FROM food_option_group
+        WHERE food_id = ANY($1)
+        ORDER BY food_id, sort_order, id;",
+    )
+    .bind(food_ids)
+    .map(map_option_group)
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    let options = sqlx::query(
+        "SELECT food_option.* FROM food_option
+        JOIN food_option_group ON food_option_group.id = food_option.group_id
+        WHERE food_option_group.food_id = ANY($1)
+        ORDER BY food_option.sort_order, food_option.id;",
+    )
+    .bind(food_ids)
+    .map(map_food_option)
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    for option in options {
+        if let Some(group) = groups.iter_mut().find(|group| group.id == option.group_id) {
+            group.options.push(option);
+        }
+    }
+
+    let mut by_food: HashMap<i32, Vec<OptionGroup>> = HashMap::new();
+    for group in groups {
+        by_food.entry(group.food_id).or_default().push(group);
+    }
+    Ok(by_food)
+}
+
+impl Store {
+    pub async fn get_option_groups(&self, food_id: i32) -> Result<Vec<OptionGroup>, Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        let mut groups = load_option_groups(&mut connection, &[food_id]).await?;
+        Ok(groups.remove(&food_id).unwrap_or_default())
+    }
+
+    // all groups of a restaurant's menu, keyed by food id
+    pub async fn get_menu_option_groups(
+        &self,
+        restaurant_id: i32,
+    ) -> Result<HashMap<i32, Vec<OptionGroup>>, Error> {
+        let food_ids = sqlx::query_scalar::<_, i32>("SELECT id FROM food WHERE restaurant_id = $1;")
+            .bind(restaurant_id)
+            .fetch_all(&self.connection)
+            .await
+            .map_err(Error::database_query_error)?;
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        load_option_groups(&mut connection, &food_ids).await
+    }
+
+    // replaces every group of the food, items already in carts keep the options and prices they were added with,
+    // checkout fails for options that are gone and asks to confirm changed prices (see `Store::checkout`)
+    pub async fn replace_option_groups(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        food_id: i32,
+        groups: Vec<NewOptionGroup>,
+    ) -> Result<Vec<OptionGroup>, Error> {
+        for group in &groups {
+            group.validate()?;
+        }
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        // also makes sure the food belongs to the restaurant
+        let price = sqlx::query_scalar::<_, i32>(
+            "SELECT price FROM food WHERE id = $1 AND restaurant_id = $2 FOR UPDATE;",
+        )
+        .bind(food_id)
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        if price as i64 + groups.iter().map(NewOptionGroup::cheapest_delta).sum::<i64>() < 0 {
+            return Err(Error::invalid_field(
+                "the options can take the price below 0".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM food_option_group WHERE food_id = $1;")
+            .bind(food_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+        let mut result = Vec::with_capacity(groups.len());
+        for (group_order, group) in groups.into_iter().enumerate() {
+            let mut new_group = sqlx::query(
+                "INSERT INTO food_option_group (food_id, name, min_select, max_select, sort_order)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING *;",
+            )
+            .bind(food_id)
+            .bind(group.name.trim())
+            .bind(group.min_select)
+            .bind(group.max_select)
+            .bind(group_order as i32)
+            .map(map_option_group)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+            for (option_order, option) in group.options.into_iter().enumerate() {
+                let option = sqlx::query(
+                    "INSERT INTO food_option (group_id, name, price_delta, available, sort_order)
+                    VALUES ($1, $2, $3, $4, $5)
+                    RETURNING *;",
+                )
+                .bind(new_group.id)
+                .bind(option.name.trim())
+                .bind(option.price_delta)
+                .bind(option.available)
+                .bind(option_order as i32)
+                .map(map_food_option)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+                new_group.options.push(option);
+            }
+            result.push(new_group);
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(result)
+    }
+}
@@ -0,0 +1,239 @@
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+
+use crate::{
+    error::Error,
+    types::gallery::{RestaurantImage, UpdateRestaurantImage},
+};
+
+use super::Store;
+
+fn map_restaurant_image(row: PgRow) -> RestaurantImage {
+    RestaurantImage {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        url: row.get("url"),
+        caption: row.get("caption"),
+        sort_order: row.get("sort_order"),
+        is_cover: row.get("is_cover"),
+        created_on: row.get("created_on"),
+    }
+}
+
+// unset the old cover first, the partial unique index allows only one cover per restaurant
+// `restaurant.image` always follows the cover so existing clients keep working
+async fn set_cover(
+    tx: &mut Transaction<'_, Postgres>,
+    restaurant_id: i32,
+    image_id: i32,
+) -> Result<(), Error> {
+    sqlx::query(
+        "UPDATE restaurant_image SET is_cover = FALSE
+        WHERE restaurant_id = $1 AND is_cover;",
+    )
+    .bind(restaurant_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    sqlx::query(
+        "UPDATE restaurant_image SET is_cover = TRUE
+        WHERE id = $1 AND restaurant_id = $2;",
+    )
+    .bind(image_id)
+    .bind(restaurant_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    sqlx::query(
+        "UPDATE restaurant
+        SET image = (SELECT url FROM restaurant_image WHERE id = $1), version = version + 1
+        WHERE id = $2;",
+    )
+    .bind(image_id)
+    .bind(restaurant_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    Ok(())
+}
+
+impl Store {
+    pub async fn get_restaurant_images(
+        &self,
+        restaurant_id: i32,
+    ) -> Result<Vec<RestaurantImage>, Error> {
+        match sqlx::query(
+            "SELECT * FROM restaurant_image
+            WHERE restaurant_id = $1
+            ORDER BY sort_order, id;",
+        )
+        .bind(restaurant_id)
+        .map(map_restaurant_image)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(images) => Ok(images),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // new images go to the end of the gallery
+    // the first image of a restaurant becomes the cover
+    pub async fn add_restaurant_image(
+        &self,
+        restaurant_id: i32,
+        url: &str,
+        caption: Option<String>,
+        cover: bool,
+    ) -> Result<RestaurantImage, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        let image = sqlx::query(
+            "INSERT INTO restaurant_image (restaurant_id, url, caption, sort_order)
+            VALUES ($1, $2, $3, (
+              SELECT COALESCE(MAX(sort_order) + 1, 0) FROM restaurant_image WHERE restaurant_id = $1
+            ))
+            RETURNING *;",
+        )
+        .bind(restaurant_id)
+        .bind(url)
+        .bind(caption)
+        .map(map_restaurant_image)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let has_cover = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM restaurant_image WHERE restaurant_id = $1 AND is_cover);",
+        )
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let is_cover = cover || !has_cover;
+        if is_cover {
+            set_cover(&mut tx, restaurant_id, image.id).await?;
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        if is_cover {
+            self.cache.invalidate_restaurant(restaurant_id);
+        }
+        Ok(RestaurantImage { is_cover, ..image })
+    }
+
+    pub async fn update_restaurant_image(
+        &self,
+        restaurant_id: i32,
+        image_id: i32,
+        update: UpdateRestaurantImage,
+    ) -> Result<RestaurantImage, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        sqlx::query(
+            "UPDATE restaurant_image SET caption = $1
+            WHERE id = $2 AND restaurant_id = $3
+            RETURNING id;",
+        )
+        .bind(update.caption)
+        .bind(image_id)
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        // a cover can only be replaced, not removed, so `is_cover: false` is ignored
+        if update.is_cover {
+            set_cover(&mut tx, restaurant_id, image_id).await?;
+        }
+
+        let image = sqlx::query("SELECT * FROM restaurant_image WHERE id = $1;")
+            .bind(image_id)
+            .map(map_restaurant_image)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        if update.is_cover {
+            self.cache.invalidate_restaurant(restaurant_id);
+        }
+        Ok(image)
+    }
+
+    // `image_ids` is the new order, images that are left out keep their relative order after them
+    pub async fn reorder_restaurant_images(
+        &self,
+        restaurant_id: i32,
+        image_ids: Vec<i32>,
+    ) -> Result<Vec<RestaurantImage>, Error> {
+        match sqlx::query(
+            "UPDATE restaurant_image
+            SET sort_order = COALESCE(array_position($1, id) - 1, cardinality($1) + sort_order)
+            WHERE restaurant_id = $2;",
+        )
+        .bind(image_ids)
+        .bind(restaurant_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => self.get_restaurant_images(restaurant_id).await,
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // returns the url so the caller can remove the file
+    // if the cover is deleted the next image in the gallery takes its place
+    pub async fn delete_restaurant_image(
+        &self,
+        restaurant_id: i32,
+        image_id: i32,
+    ) -> Result<String, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        let (url, was_cover): (String, bool) = sqlx::query(
+            "DELETE FROM restaurant_image
+            WHERE id = $1 AND restaurant_id = $2
+            RETURNING url, is_cover;",
+        )
+        .bind(image_id)
+        .bind(restaurant_id)
+        .map(|row: PgRow| (row.get("url"), row.get("is_cover")))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        if was_cover {
+            let next = sqlx::query_scalar::<_, i32>(
+                "SELECT id FROM restaurant_image
+                WHERE restaurant_id = $1
+                ORDER BY sort_order, id
+                LIMIT 1;",
+            )
+            .bind(restaurant_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+            match next {
+                Some(next) => set_cover(&mut tx, restaurant_id, next).await?,
+                None => {
+                    sqlx::query("UPDATE restaurant SET image = '', version = version + 1 WHERE id = $1;")
+                        .bind(restaurant_id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(Error::database_query_error)?;
+                }
+            }
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        if was_cover {
+            self.cache.invalidate_restaurant(restaurant_id);
+        }
+        Ok(url)
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use sqlx::{postgres::PgRow, Row};
+
+use crate::{
+    error::Error,
+    types::menu::{plan_import, ImportReport, MenuChange, MenuRow, RowError},
+};
+
+use super::{price_history::record_price, Store};
+
+impl Store {
+    pub async fn export_menu(&self, restaurant_id: i32) -> Result<Vec<MenuRow>, Error> {
+        match sqlx::query(
+            "SELECT id, name, image, tag, price, ingredient, available
+            FROM food
+            WHERE restaurant_id = $1
+            ORDER BY id",
+        )
+        .bind(restaurant_id)
+        .map(|row: PgRow| MenuRow {
+            id: row.get("id"),
+            name: row.get("name"),
+            image: row.get("image"),
+            tag: row.get("tag"),
+            price: row.get("price"),
+            ingredient: row.get("ingredient"),
+            available: row.get("available"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rows) => Ok(rows),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // nothing is written unless every row is valid, and then all of it is written in one transaction
+    // `tag` is linked to the managed tag with the same slug, if there is one
+    pub async fn import_menu(
+        &self,
+        restaurant_id: i32,
+        rows: Vec<Result<MenuRow, RowError>>,
+        dry_run: bool,
+    ) -> Result<ImportReport, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        // also makes sure the restaurant exists, and keeps two imports from racing
+        sqlx::query("SELECT id FROM restaurant WHERE id = $1 FOR UPDATE")
+            .bind(restaurant_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+        let existing: HashMap<i32, String> =
+            sqlx::query("SELECT id, name FROM food WHERE restaurant_id = $1")
+                .bind(restaurant_id)
+                .map(|row: PgRow| (row.get("id"), row.get("name")))
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?
+                .into_iter()
+                .collect();
+
+        let mut report = ImportReport {
+            dry_run,
+            rows: rows.len(),
+            ..Default::default()
+        };
+        let (changes, errors) = plan_import(&existing, rows);
+        for change in &changes {
+            match change {
+                MenuChange::Create(_) => report.created += 1,
+                MenuChange::Update(..) => report.updated += 1,
+            }
+        }
+        report.errors = errors;
+        if dry_run || !report.errors.is_empty() {
+            return Ok(report);
+        }
+
+        for change in changes {
+            let (id, row) = match change {
+                MenuChange::Create(row) => (None, row),
+                MenuChange::Update(id, row) => (Some(id), row),
+            };
+            let query = sqlx::query(match id {
+                None => {
+                    "INSERT INTO food (name, image, tag, tag_id, price, ingredient, available, restaurant_id)
+                    VALUES ($1, $2, $3, (SELECT id FROM tag WHERE slug = $3), $4, $5, $6, $7)
+                    RETURNING id"
+                }
+                Some(_) => {
+                    "UPDATE food
+                    SET name = $1, image = $2, tag = $3, tag_id = (SELECT id FROM tag WHERE slug = $3),
+                    price = $4, ingredient = $5, available = $6,
+                    version = version + 1
+                    WHERE restaurant_id = $7 AND id = $8
+                    RETURNING id"
+                }
+            })
+            .bind(row.name.trim().to_string())
+            .bind(row.image)
+            .bind(row.tag.trim().to_string())
+            .bind(row.price)
+            .bind(row.ingredient)
+            .bind(row.available)
+            .bind(restaurant_id);
+            let query = match id {
+                Some(id) => query.bind(id),
+                None => query,
+            };
+            let food_id: i32 = query
+                .map(|row: PgRow| row.get("id"))
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+            record_price(&mut *tx, food_id).await?;
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        report.applied = true;
+        Ok(report)
+    }
+}
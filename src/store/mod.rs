@@ -1,22 +1,41 @@
 use std::time::Duration;
-use crate::utils::colors::ansi::*;
+use crate::utils::{cache::ResponseCache, colors::ansi::*};
 use sqlx::{postgres::PgPoolOptions, Error, PgPool};
 use text_io::read;
 
 pub mod auth;
+pub mod availability;
+pub mod bundle;
 pub mod comment;
+pub mod delivery;
+pub mod favorite;
 pub mod food;
+pub mod food_option;
+pub mod gallery;
+pub mod menu;
 pub mod open_hours;
 pub mod order;
+pub mod order_status;
 pub mod owner;
 pub mod payment;
+pub mod price_history;
+pub mod promotion;
+pub mod recommendation;
 pub mod restaurant;
+pub mod review;
 pub mod search;
+pub mod section;
+pub mod stats;
+pub mod stock;
+pub mod tag;
 pub mod utils;
+pub mod version;
 
 #[derive(Clone, Debug)]
 pub struct Store {
     pub connection: PgPool,
+    // cleared by the store functions that change restaurants, food or hours
+    pub cache: ResponseCache,
 }
 
 impl Store {
@@ -61,6 +80,7 @@ impl Store {
         
         Store {
             connection: db_pool,
+            cache: ResponseCache::default(),
         }
     }
 }
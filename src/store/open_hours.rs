@@ -1,26 +1,83 @@
 use super::Store;
 use crate::{
     error::Error,
-    types::{account::Role, restaurant::OpenHours},
-    verify_roles,
+    types::{
+        restaurant::{
+            intervals_on, is_open_at, validate_week, HoursException, HoursInterval,
+            NewHoursException, NewOpenHours, OpenHours, OpenStatus, Weekday,
+        },
+    },
+    utils::etag::IfMatch,
 };
-use sqlx::Row;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
 use uuid::Uuid;
 
+fn map_open_hours(row: PgRow) -> OpenHours {
+    OpenHours {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        day_of_week: row.get("day_of_week"),
+        open_time: row.get("open_time"),
+        close_time: row.get("close_time"),
+        version: row.get("version"),
+    }
+}
+
+// the interval being changed has to be in the week, `If-Match` is the week's ETag
+// like everywhere else for hours, since that's the one `GET /restaurants/hours/{id}` sends
+fn check_interval(week: &[OpenHours], id: i32, if_match: &IfMatch) -> Result<(), Error> {
+    match week.iter().any(|h| h.id == id) {
+        true => if_match.check(&OpenHours::week_etag(week)),
+        false => Err(Error::database_query_error(sqlx::Error::RowNotFound)),
+    }
+}
+
+fn map_hours_exception(row: PgRow) -> HoursException {
+    HoursException {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        closed: row.get("closed"),
+        open_time: row.get("open_time"),
+        close_time: row.get("close_time"),
+        reason: row.get("reason"),
+    }
+}
+
+// locks the restaurant row so two writers can't insert overlapping intervals at the same time
+// returns the current week, which the caller validates together with its change
+async fn lock_week(
+    tx: &mut Transaction<'_, Postgres>,
+    restaurant_id: i32,
+) -> Result<Vec<OpenHours>, Error> {
+    sqlx::query("SELECT id FROM restaurant WHERE id = $1 FOR UPDATE;")
+        .bind(restaurant_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+    sqlx::query(
+        "SELECT * FROM restaurant_hours
+        WHERE restaurant_id = $1;",
+    )
+    .bind(restaurant_id)
+    .map(map_open_hours)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)
+}
+
 impl Store {
     pub async fn get_restaurant_hours(&self, restaurant_id: i32) -> Result<Vec<OpenHours>, Error> {
         match sqlx::query(
-            "SELECT day_of_week, open_time, close_time FROM restaurant_hours
+            "SELECT id, restaurant_id, day_of_week, open_time, close_time, version FROM restaurant_hours
             WHERE restaurant_id = $1
-            ORDER BY day_of_week; ",
+            ORDER BY day_of_week, open_time; ",
         )
         .bind(restaurant_id)
-        .map(|row| OpenHours {
-            restaurant_id: row.get("restaurant_id"),
-            day_of_week: row.get("day_of_week"),
-            open_time: row.get("open_time"),
-            close_time: row.get("close_time"),
-        })
+        .map(map_open_hours)
         .fetch_all(&self.connection)
         .await
         {
@@ -32,30 +89,44 @@ impl Store {
     pub async fn post_restaurant_hours(
         &self,
         account_id: Uuid,
-        hours: OpenHours,
+        hours: NewOpenHours,
     ) -> Result<OpenHours, Error> {
-        match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
-            Ok(true) => {
-                match sqlx::query(
-                "INSERT INTO restaurant_hours (restaurant_id, day_of_week, open_time, close_time)
-                VALUES ($1, $2, $3, $4);"
-              )
-              .bind(hours.restaurant_id)
-              .bind(hours.day_of_week)
-              .bind(hours.open_time)
-              .bind(hours.close_time)
-              .map(|row| OpenHours {
-                restaurant_id: row.get("restaurant_id"),
-                day_of_week: row.get("day_of_week"),
-                open_time: row.get("open_time"),
-                close_time: row.get("close_time"),
-            })
-            .fetch_one(&self.connection)
+        match self
+            .verify_restaurant_modification_access(hours.restaurant_id, &account_id)
             .await
-            {
-                Ok(hours) => Ok(hours),
-                Err(e) => Err(Error::database_query_error(e)),
-            }
+        {
+            Ok(true) => {
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+                let mut week: Vec<HoursInterval> = lock_week(&mut tx, hours.restaurant_id)
+                    .await?
+                    .iter()
+                    .map(HoursInterval::from)
+                    .collect();
+                week.push(HoursInterval {
+                    day_of_week: hours.day_of_week.clone(),
+                    open_time: hours.open_time,
+                    close_time: hours.close_time,
+                });
+                validate_week(&week)?;
+
+                let hours = sqlx::query(
+                    "INSERT INTO restaurant_hours (restaurant_id, day_of_week, open_time, close_time)
+                    VALUES ($1, $2, $3, $4)
+                    RETURNING *;",
+                )
+                .bind(hours.restaurant_id)
+                .bind(hours.day_of_week)
+                .bind(hours.open_time)
+                .bind(hours.close_time)
+                .map(map_open_hours)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_hours(hours.restaurant_id);
+                Ok(hours)
             }
             Ok(false) => Err(Error::not_authorized),
             Err(e) => Err(e),
@@ -66,31 +137,50 @@ impl Store {
         &self,
         account_id: Uuid,
         hours: OpenHours,
-    ) -> Result<OpenHours, Error> {
-        match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
+        if_match: &IfMatch,
+    ) -> Result<(OpenHours, String), Error> {
+        match self
+            .verify_restaurant_modification_access(hours.restaurant_id, &account_id)
+            .await
+        {
             Ok(true) => {
-                match sqlx::query(
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+                let current = lock_week(&mut tx, hours.restaurant_id).await?;
+                check_interval(&current, hours.id, if_match)?;
+                let mut week: Vec<HoursInterval> = current
+                    .iter()
+                    .filter(|h| h.id != hours.id)
+                    .map(HoursInterval::from)
+                    .collect();
+                week.push(HoursInterval::from(&hours));
+                validate_week(&week)?;
+
+                let hours = sqlx::query(
                     "UPDATE restaurant_hours
-                    SET open_time = $1, close_time = $2
-                    WHERE restaurant_id = $3
-                    AND day_of_week = $4;",
+                    SET day_of_week = $1, open_time = $2, close_time = $3, version = version + 1
+                    WHERE id = $4
+                    AND restaurant_id = $5
+                    RETURNING *;",
                 )
+                .bind(hours.day_of_week)
                 .bind(hours.open_time)
                 .bind(hours.close_time)
+                .bind(hours.id)
                 .bind(hours.restaurant_id)
-                .bind(hours.day_of_week)
-                .map(|row| OpenHours {
-                    restaurant_id: row.get("restaurant_id"),
-                    day_of_week: row.get("day_of_week"),
-                    open_time: row.get("open_time"),
-                    close_time: row.get("close_time"),
-                })
-                .fetch_one(&self.connection)
+                .map(map_open_hours)
+                .fetch_one(&mut *tx)
                 .await
-                {
-                    Ok(hours) => Ok(hours),
-                    Err(e) => Err(Error::database_query_error(e)),
-                }
+                .map_err(Error::database_query_error)?;
+
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_hours(hours.restaurant_id);
+
+                // the week's new ETag, for the next write
+                let mut week = current;
+                week.retain(|h| h.id != hours.id);
+                week.push(hours.clone());
+                Ok((hours, OpenHours::week_etag(&week)))
             }
             Ok(false) => Err(Error::not_authorized),
             Err(e) => Err(e),
@@ -101,18 +191,212 @@ impl Store {
         &self,
         account_id: Uuid,
         hours: OpenHours,
+        if_match: &IfMatch,
     ) -> Result<bool, Error> {
-        match verify_roles!(&self, &account_id, Role::admin, Role::restaurant_owner).await {
+        match self
+            .verify_restaurant_modification_access(hours.restaurant_id, &account_id)
+            .await
+        {
             Ok(true) => {
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+                let current = lock_week(&mut tx, hours.restaurant_id).await?;
+                check_interval(&current, hours.id, if_match)?;
+
                 match sqlx::query(
                     "DELETE FROM restaurant_hours 
-                    WHERE restaurant_id = $1
-                    AND day_of_week = $2;",
+                    WHERE id = $1
+                    AND restaurant_id = $2;",
                 )
+                .bind(hours.id)
                 .bind(hours.restaurant_id)
-                .bind(hours.day_of_week)
-                .execute(&self.connection)
+                .execute(&mut *tx)
                 .await
+                {
+                    Ok(_) => {
+                        tx.commit().await.map_err(Error::database_query_error)?;
+                        self.cache.invalidate_hours(hours.restaurant_id);
+                        Ok(true)
+                    }
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    // replaces the whole week in one transaction
+    // an empty list clears the schedule, `If-Match` is checked against `OpenHours::week_etag`
+    pub async fn replace_restaurant_hours(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        week: Vec<HoursInterval>,
+        if_match: &IfMatch,
+    ) -> Result<Vec<OpenHours>, Error> {
+        match self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                validate_week(&week)?;
+
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+                let current = lock_week(&mut tx, restaurant_id).await?;
+                if_match.check(&OpenHours::week_etag(&current))?;
+
+                sqlx::query("DELETE FROM restaurant_hours WHERE restaurant_id = $1;")
+                    .bind(restaurant_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Error::database_query_error)?;
+
+                let mut hours = Vec::with_capacity(week.len());
+                for interval in week {
+                    let row = sqlx::query(
+                        "INSERT INTO restaurant_hours (restaurant_id, day_of_week, open_time, close_time)
+                        VALUES ($1, $2, $3, $4)
+                        RETURNING *;",
+                    )
+                    .bind(restaurant_id)
+                    .bind(interval.day_of_week)
+                    .bind(interval.open_time)
+                    .bind(interval.close_time)
+                    .map(map_open_hours)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(Error::database_query_error)?;
+                    hours.push(row);
+                }
+
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_hours(restaurant_id);
+                Ok(hours)
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn get_hours_exceptions(
+        &self,
+        restaurant_id: i32,
+    ) -> Result<Vec<HoursException>, Error> {
+        match sqlx::query(
+            "SELECT * FROM restaurant_hours_exception
+            WHERE restaurant_id = $1
+            ORDER BY start_date;",
+        )
+        .bind(restaurant_id)
+        .map(map_hours_exception)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(exceptions) => Ok(exceptions),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn post_hours_exception(
+        &self,
+        account_id: Uuid,
+        exception: NewHoursException,
+    ) -> Result<HoursException, Error> {
+        exception.validate()?;
+        match self
+            .verify_restaurant_modification_access(exception.restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                match sqlx::query(
+                    "INSERT INTO restaurant_hours_exception
+                    (restaurant_id, start_date, end_date, closed, open_time, close_time, reason)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING *;",
+                )
+                .bind(exception.restaurant_id)
+                .bind(exception.start_date)
+                .bind(exception.end_date)
+                .bind(exception.closed)
+                .bind(exception.open_time)
+                .bind(exception.close_time)
+                .bind(exception.reason)
+                .map(map_hours_exception)
+                .fetch_one(&self.connection)
+                .await
+                {
+                    Ok(exception) => Ok(exception),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn put_hours_exception(
+        &self,
+        account_id: Uuid,
+        exception: HoursException,
+    ) -> Result<HoursException, Error> {
+        exception.validate()?;
+        match self
+            .verify_restaurant_modification_access(exception.restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                match sqlx::query(
+                    "UPDATE restaurant_hours_exception
+                    SET start_date = $1, end_date = $2, closed = $3, open_time = $4, close_time = $5, reason = $6
+                    WHERE id = $7 AND restaurant_id = $8
+                    RETURNING *;",
+                )
+                .bind(exception.start_date)
+                .bind(exception.end_date)
+                .bind(exception.closed)
+                .bind(exception.open_time)
+                .bind(exception.close_time)
+                .bind(exception.reason)
+                .bind(exception.id)
+                .bind(exception.restaurant_id)
+                .map(map_hours_exception)
+                .fetch_one(&self.connection)
+                .await
+                {
+                    Ok(exception) => Ok(exception),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn delete_hours_exception(
+        &self,
+        account_id: Uuid,
+        exception_id: i32,
+    ) -> Result<bool, Error> {
+        let restaurant_id = match sqlx::query_scalar::<_, i32>(
+            "SELECT restaurant_id FROM restaurant_hours_exception WHERE id = $1;",
+        )
+        .bind(exception_id)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(id) => id,
+            Err(e) => return Err(Error::database_query_error(e)),
+        };
+
+        match self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await
+        {
+            Ok(true) => {
+                match sqlx::query("DELETE FROM restaurant_hours_exception WHERE id = $1;")
+                    .bind(exception_id)
+                    .execute(&self.connection)
+                    .await
                 {
                     Ok(_) => Ok(true),
                     Err(e) => Err(Error::database_query_error(e)),
@@ -122,4 +406,68 @@ impl Store {
             Err(e) => Err(e),
         }
     }
+
+    // the shortest exception covering the date wins, so a one day "special hours"
+    // entry inside a week long closure still applies
+    async fn get_exception_for_date(
+        &self,
+        restaurant_id: i32,
+        date: NaiveDate,
+    ) -> Result<Option<HoursException>, Error> {
+        match sqlx::query(
+            "SELECT * FROM restaurant_hours_exception
+            WHERE restaurant_id = $1
+            AND $2 BETWEEN start_date AND end_date
+            ORDER BY end_date - start_date, id DESC
+            LIMIT 1;",
+        )
+        .bind(restaurant_id)
+        .bind(date)
+        .map(map_hours_exception)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(exception) => Ok(exception),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // exceptions are checked first, the weekly schedule is only a fallback
+    // yesterday is needed for hours that close after midnight, its exception decides those too
+    pub async fn get_open_status(
+        &self,
+        restaurant_id: i32,
+        at: NaiveDateTime,
+    ) -> Result<OpenStatus, Error> {
+        let date = at.date();
+        let yesterday = date - Duration::days(1);
+        let exception = self.get_exception_for_date(restaurant_id, date).await?;
+        let yesterday_exception = self.get_exception_for_date(restaurant_id, yesterday).await?;
+
+        let today = Weekday::from(at.weekday());
+        match sqlx::query(
+            "SELECT * FROM restaurant_hours
+            WHERE restaurant_id = $1
+            AND day_of_week IN ($2, $3);",
+        )
+        .bind(restaurant_id)
+        .bind(today.clone())
+        .bind(today.previous())
+        .map(map_open_hours)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(hours) => {
+                let week: Vec<HoursInterval> = hours.iter().map(HoursInterval::from).collect();
+                let mut intervals = intervals_on(yesterday, yesterday_exception.as_ref(), &week);
+                intervals.extend(intervals_on(date, exception.as_ref(), &week));
+                Ok(OpenStatus {
+                    restaurant_id,
+                    is_open: is_open_at(&intervals, &today, at.time()),
+                    reason: exception.and_then(|exception| exception.reason),
+                })
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
 }
@@ -1,8 +1,34 @@
+use std::collections::HashMap;
+
 use uuid::Uuid;
-use sqlx::{postgres::PgRow, Row};
-use crate::{error::Error, types::orders::{GetOrder, Item, NewItem}};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use crate::{
+    error::Error,
+    types::{
+        bundle::{choices_key, ItemComponent},
+        delivery::{Deliverability, DeliveryAddress, DeliveryCheck},
+        food::Food,
+        food_option::{chosen_key, options_key, select_options, ItemOption},
+        orders::{GetOrder, Item, NewItem, OrderStatus, StatusActor},
+    },
+};
+
+use super::{
+    availability::apply_schedules_on, bundle::load_bundle, delivery::deliverability, food::map_food,
+    food_option::load_option_groups, order_status::record_status, promotion::apply_promotions_on,
+    stock::take_stock, Store,
+};
 
-use super::Store;
+// what checkout needs to know about an item of the cart
+struct CartItem {
+    id: i32,
+    restaurant_id: i32,
+    food_id: Option<i32>,
+    bundle_id: Option<i32>,
+    name: String,
+    price: i32,
+    discount_price: Option<i32>,
+}
 
 
 impl Store {
@@ -13,12 +39,13 @@ impl Store {
           "SELECT
             orders.id AS order_id,
             orders.status,
-            COALESCE(SUM(item.price * item.quantity), 0) AS total_price,
-            SUM(item.discount_price * item.quantity) AS total_discounted_price
+            orders.delivery_fee,
+            (COALESCE(SUM((item.price + item.options_price) * item.quantity), 0) + orders.delivery_fee)::INT AS total_price,
+            (COALESCE(SUM((COALESCE(item.discount_price, item.price) + item.options_price) * item.quantity), 0) + orders.delivery_fee)::INT AS total_discounted_price
           FROM orders 
-          LEFT JOIN items ON orders.id = item.order_id
-          WHERE orders.id = $1 OR (orders.account_id = $2 AND orders.status IN ('cart', 'pending'))
-          GROUP BY orders.id, orders.status;"
+          LEFT JOIN item ON orders.id = item.order_id
+          WHERE orders.id = $1 AND orders.account_id = $2
+          GROUP BY orders.id, orders.status, orders.delivery_fee;"
         )
         .bind(order_id)
         .bind(account_id)
@@ -28,6 +55,48 @@ impl Store {
           Err(e) => return Err(Error::database_query_error(e))
         };
 
+      let mut options: HashMap<i32, Vec<ItemOption>> = HashMap::new();
+      let option_rows = sqlx::query(
+        "SELECT item_option.*
+        FROM item_option
+        JOIN item ON item.id = item_option.item_id
+        WHERE item.order_id = $1
+        ORDER BY item_option.id;"
+      )
+      .bind(order_id)
+      .map(|row: PgRow| (row.get::<i32, _>("item_id"), ItemOption {
+        option_id: row.get("option_id"),
+        group_name: row.get("group_name"),
+        name: row.get("name"),
+        price_delta: row.get("price_delta"),
+      }))
+      .fetch_all(&self.connection).await
+      .map_err(Error::database_query_error)?;
+      for (item_id, option) in option_rows {
+        options.entry(item_id).or_default().push(option);
+      }
+
+      let mut components: HashMap<i32, Vec<ItemComponent>> = HashMap::new();
+      let component_rows = sqlx::query(
+        "SELECT item_component.*
+        FROM item_component
+        JOIN item ON item.id = item_component.item_id
+        WHERE item.order_id = $1
+        ORDER BY item_component.id;"
+      )
+      .bind(order_id)
+      .map(|row: PgRow| (row.get::<i32, _>("item_id"), ItemComponent {
+        food_id: row.get("food_id"),
+        slot_name: row.get("slot_name"),
+        name: row.get("name"),
+        quantity: row.get("quantity"),
+      }))
+      .fetch_all(&self.connection).await
+      .map_err(Error::database_query_error)?;
+      for (item_id, component) in component_rows {
+        components.entry(item_id).or_default().push(component);
+      }
+
       let items = match sqlx::query(
         "SELECT *
         FROM item
@@ -40,11 +109,15 @@ impl Store {
         order_id: row.get("order_id"),
         restaurant_id: row.get("restaurant_id"),
         food_id: row.get("food_id"),
+        bundle_id: row.get("bundle_id"),
         quantity: row.get("quantity"),
         name: row.get("name"),
         image: row.get("image"),
         price: row.get("price"),
         discount_price: row.get("discount_price"),
+        options_price: row.get("options_price"),
+        options: options.remove(&row.get::<i32, _>("id")).unwrap_or_default(),
+        components: components.remove(&row.get::<i32, _>("id")).unwrap_or_default(),
       })
       .fetch_all(&self.connection).await        
       {
@@ -57,6 +130,7 @@ impl Store {
         account_id,
         order_id,
         status: order.get("status"),
+        delivery_fee: order.get("delivery_fee"),
         total_price: order.get("total_price"),
         total_discounted_price: order.get("total_discounted_price"),
       };
@@ -94,49 +168,604 @@ impl Store {
         }
     }
 
-    // we insert an item
+    // we insert an item into the account's cart, a cart is made when there is none
     // if it exist we update "quantity" based on the given count 
-    // after each insert we check the "quantity", if it reaches 0 we delete the item
+    // after each insert we check the "quantity", if it reaches 0 (or less) we delete the item
+    // the same food with other options is another item, `options_key` keeps them apart
+    // adding a food outside its availability windows is rejected, taking it out of the cart never is
+    // name, image and price are the server's at the time of adding: `price`, and `discount_price` when
+    // a running promotion makes it cheaper. adding more of an item keeps the price it was added at,
+    // `checkout` asks to confirm it when it changed since
     // i dont think its necessary to add role checks to this, so user can use cart freely
     // we need to require auth for payment section
     pub async fn add_to_cart(&self, account_id: Uuid, item: NewItem) -> Result<bool, Error> {
+      let order_id = self.create_cart(account_id).await?;
+      let food_id = match (item.food_id, item.bundle_id) {
+        (Some(food_id), None) => food_id,
+        (None, Some(bundle_id)) => return self.add_bundle_to_cart(account_id, order_id, bundle_id, item).await,
+        (None, None) => return Err(Error::missing_parameters),
+        (Some(_), Some(_)) => return Err(Error::invalid_field("set either food_id or bundle_id".to_string())),
+      };
+      // taking some away always works, even when the food or its options are gone from the menu
+      if item.quantity <= 0 {
+        return self.reduce_cart_item(order_id, Some(food_id), None, &chosen_key(&item.options), item.quantity).await;
+      }
+      // the food, its options, promotions and windows are all read in the transaction the item is written in
       let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
-      sqlx::query(
+      let groups = load_option_groups(&mut tx, &[food_id]).await?.remove(&food_id).unwrap_or_default();
+      let options = select_options(&groups, &item.options)?;
+      let options_price: i32 = options.iter().map(|option| option.price_delta).sum();
+
+      // not locked, for the same reason as in `reprice_cart`
+      let mut food = sqlx::query("SELECT * FROM food WHERE id = $1;")
+        .bind(food_id)
+        .map(map_food)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+      apply_promotions_on(&mut tx, std::slice::from_mut(&mut food)).await?;
+      apply_schedules_on(&mut tx, std::slice::from_mut(&mut food)).await?;
+      if !food.orderable_now {
+        return Err(Error::not_orderable(food.name));
+      }
+      if food.effective_price + options_price < 0 {
+        return Err(Error::invalid_options(format!("the options take `{}` below 0", food.name)));
+      }
+      let discount_price = (food.effective_price < food.price).then_some(food.effective_price);
+
+      let (item_id, quantity, inserted): (i32, i32, bool) = sqlx::query(
           "INSERT INTO item (
               order_id, account_id, restaurant_id, food_id,
-              quantity, name, image, price, discount_price
+              quantity, name, image, price, discount_price, options_price, options_key
           )
-          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-          ON CONFLICT (order_id, food_id)
-          DO UPDATE SET quantity = item.quantity + EXCLUDED.quantity"
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+          ON CONFLICT (order_id, food_id, options_key)
+          DO UPDATE SET quantity = item.quantity + EXCLUDED.quantity
+          RETURNING id, quantity, (xmax = 0) AS inserted"
       )
-      .bind(item.order_id)
+      .bind(order_id)
       .bind(account_id)
-      .bind(item.restaurant_id)
-      .bind(item.food_id)
+      .bind(food.restaurant_id)
+      .bind(food_id)
       .bind(item.quantity)
-      .bind(item.name)
-      .bind(item.image)
-      .bind(item.price)
-      .bind(item.discount_price)
-      .execute(&mut *tx)
+      .bind(&food.name)
+      .bind(&food.image)
+      .bind(food.price)
+      .bind(discount_price)
+      .bind(options_price)
+      .bind(options_key(&options))
+      .map(|row: PgRow| (row.get("id"), row.get("quantity"), row.get("inserted")))
+      .fetch_one(&mut *tx)
       .await
       .map_err(Error::database_query_error)?;
 
-      sqlx::query(
-          "DELETE FROM item WHERE order_id = $1 AND food_id = $2 AND quantity = 0"
+      // a new item keeps a copy of its options, a merged one already has them
+      if inserted {
+        for option in options {
+          sqlx::query(
+              "INSERT INTO item_option (item_id, option_id, group_name, name, price_delta)
+              VALUES ($1, $2, $3, $4, $5)"
+          )
+          .bind(item_id)
+          .bind(option.option_id)
+          .bind(option.group_name)
+          .bind(option.name)
+          .bind(option.price_delta)
+          .execute(&mut *tx)
+          .await
+          .map_err(Error::database_query_error)?;
+        }
+      }
+
+      if quantity <= 0 {
+        sqlx::query("DELETE FROM item WHERE id = $1")
+        .bind(item_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+      }
+  
+      tx.commit().await.map_err(Error::database_query_error)?;
+  
+      Ok(true)
+  }
+
+    // a bundle goes in as one item at the bundle's price, its foods are kept in `item_component`
+    // so the kitchen sees what to prepare, and so their stock is taken at checkout
+    // every food of it has to be inside its availability windows
+    async fn add_bundle_to_cart(
+      &self,
+      account_id: Uuid,
+      order_id: i32,
+      bundle_id: i32,
+      item: NewItem,
+    ) -> Result<bool, Error> {
+      // like foods, taking a bundle away doesn't look at the bundle, it may be gone
+      if item.quantity <= 0 {
+        return self.reduce_cart_item(order_id, None, Some(bundle_id), &choices_key(&item.choices), item.quantity).await;
+      }
+      // the bundle, its foods and their windows are read in the transaction the item is written in
+      let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+      let restaurant_id = sqlx::query_scalar::<_, i32>("SELECT restaurant_id FROM bundle WHERE id = $1;")
+        .bind(bundle_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+      let bundle = load_bundle(&mut tx, restaurant_id, bundle_id).await?;
+      let components = bundle.components(&item.choices)?;
+      let food_ids: Vec<i32> = components.iter().filter_map(|component| component.food_id).collect();
+      let mut foods = sqlx::query("SELECT * FROM food WHERE id = ANY($1);")
+        .bind(&food_ids)
+        .map(map_food)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+      apply_schedules_on(&mut tx, &mut foods).await?;
+      if let Some(food) = foods.into_iter().find(|food| !food.orderable_now) {
+        return Err(Error::not_orderable(food.name));
+      }
+
+      let (item_id, quantity, inserted): (i32, i32, bool) = sqlx::query(
+          "INSERT INTO item (
+              order_id, account_id, restaurant_id, bundle_id,
+              quantity, name, image, price, discount_price, options_key
+          )
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NULL, $9)
+          ON CONFLICT (order_id, bundle_id, options_key)
+          DO UPDATE SET quantity = item.quantity + EXCLUDED.quantity
+          RETURNING id, quantity, (xmax = 0) AS inserted"
       )
-      .bind(item.order_id)
-      .bind(item.food_id)
-      .execute(&mut *tx)
+      .bind(order_id)
+      .bind(account_id)
+      .bind(restaurant_id)
+      .bind(bundle_id)
+      .bind(item.quantity)
+      .bind(&bundle.name)
+      .bind(&bundle.image)
+      .bind(bundle.price)
+      .bind(choices_key(&item.choices))
+      .map(|row: PgRow| (row.get("id"), row.get("quantity"), row.get("inserted")))
+      .fetch_one(&mut *tx)
       .await
       .map_err(Error::database_query_error)?;
-  
+
+      if inserted {
+        for component in components {
+          sqlx::query(
+              "INSERT INTO item_component (item_id, food_id, slot_name, name, quantity)
+              VALUES ($1, $2, $3, $4, $5)"
+          )
+          .bind(item_id)
+          .bind(component.food_id)
+          .bind(component.slot_name)
+          .bind(component.name)
+          .bind(component.quantity)
+          .execute(&mut *tx)
+          .await
+          .map_err(Error::database_query_error)?;
+        }
+      }
+
+      if quantity <= 0 {
+        sqlx::query("DELETE FROM item WHERE id = $1")
+        .bind(item_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+      }
+
       tx.commit().await.map_err(Error::database_query_error)?;
-  
+
       Ok(true)
   }
-  
 
+    // a negative (or zero) quantity only finds the item by its stored key, nothing is checked
+    // against the menu, and the item goes away when it reaches 0
+    async fn reduce_cart_item(
+      &self,
+      order_id: i32,
+      food_id: Option<i32>,
+      bundle_id: Option<i32>,
+      key: &str,
+      quantity: i32,
+    ) -> Result<bool, Error> {
+      let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+      let item = sqlx::query(
+          "UPDATE item SET quantity = quantity + $1
+          WHERE order_id = $2 AND (food_id = $3 OR bundle_id = $4) AND options_key = $5
+          RETURNING id, quantity;"
+      )
+      .bind(quantity)
+      .bind(order_id)
+      .bind(food_id)
+      .bind(bundle_id)
+      .bind(key)
+      .map(|row: PgRow| (row.get::<i32, _>("id"), row.get::<i32, _>("quantity")))
+      .fetch_optional(&mut *tx)
+      .await
+      .map_err(Error::database_query_error)?;
+
+      if let Some((item_id, quantity)) = item {
+        if quantity <= 0 {
+          sqlx::query("DELETE FROM item WHERE id = $1")
+          .bind(item_id)
+          .execute(&mut *tx)
+          .await
+          .map_err(Error::database_query_error)?;
+        }
+      }
+      tx.commit().await.map_err(Error::database_query_error)?;
+      Ok(true)
+  }
+
+    // takes an item out of the account's cart whatever it holds, the only way out for an item
+    // whose food or bundle was deleted since neither can be named any more
+    pub async fn remove_cart_item(&self, account_id: Uuid, item_id: i32) -> Result<(), Error> {
+      sqlx::query_scalar::<_, i32>(
+          "DELETE FROM item USING orders
+          WHERE item.id = $1 AND orders.id = item.order_id
+          AND orders.account_id = $2 AND orders.status = 'cart'
+          RETURNING item.id;"
+      )
+      .bind(item_id)
+      .bind(account_id)
+      .fetch_optional(&self.connection)
+      .await
+      .map_err(Error::database_query_error)?
+      .map(|_| ())
+      .ok_or(Error::database_query_error(sqlx::Error::RowNotFound))
+  }
+
+    // the cart's restaurant must deliver to the address and the cart must reach the zone's minimum
+    // the zone fee is stored on the order and added to its totals
+    pub async fn set_order_delivery(
+        &self,
+        account_id: Uuid,
+        order_id: i32,
+        address: DeliveryAddress,
+    ) -> Result<Deliverability, Error> {
+        let restaurants: Vec<(i32, i32)> = sqlx::query(
+            "SELECT
+              restaurant_id,
+              COALESCE(SUM((COALESCE(discount_price, price) + options_price) * quantity), 0)::INT AS subtotal
+            FROM item
+            WHERE order_id = $1 AND account_id = $2
+            GROUP BY restaurant_id;",
+        )
+        .bind(order_id)
+        .bind(account_id)
+        .map(|row: PgRow| (row.get("restaurant_id"), row.get("subtotal")))
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let (restaurant_id, subtotal) = match restaurants.as_slice() {
+            [] => return Err(Error::empty_cart),
+            [restaurant] => *restaurant,
+            _ => return Err(Error::multiple_restaurants),
+        };
+
+        let check = self
+            .check_deliverability(
+                restaurant_id,
+                DeliveryCheck {
+                    location: address.location,
+                    subtotal: Some(subtotal),
+                },
+            )
+            .await?;
 
-}
\ No newline at end of file
+        let (zone_id, fee) = match (check.zone_id, check.fee) {
+            (Some(zone_id), Some(fee)) => (zone_id, fee),
+            _ => return Err(Error::not_deliverable),
+        };
+        if check.meets_minimum == Some(false) {
+            return Err(Error::below_minimum_order(check.minimum_order.unwrap_or_default()));
+        }
+
+        sqlx::query(
+            "UPDATE orders
+            SET delivery_fee = $1, delivery_zone_id = $2, delivery_location = $3
+            WHERE id = $4 AND account_id = $5 AND status = 'cart'
+            RETURNING id;",
+        )
+        .bind(fee)
+        .bind(zone_id)
+        .bind(address.location)
+        .bind(order_id)
+        .bind(account_id)
+        .fetch_one(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        Ok(check)
+    }
+
+    // back to a pickup order: the location, zone and fee are taken off the cart
+    pub async fn clear_order_delivery(&self, account_id: Uuid, order_id: i32) -> Result<(), Error> {
+        sqlx::query_scalar::<_, i32>(
+            "UPDATE orders
+            SET delivery_fee = 0, delivery_zone_id = NULL, delivery_location = NULL
+            WHERE id = $1 AND account_id = $2 AND status = 'cart'
+            RETURNING id;",
+        )
+        .bind(order_id)
+        .bind(account_id)
+        .fetch_optional(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?
+        .map(|_| ())
+        .ok_or(Error::database_query_error(sqlx::Error::RowNotFound))
+    }
+
+    // checks the cart again and brings its prices up to date, a cart can sit for days
+    // every food has to be orderable now with options that are still offered, every bundle too,
+    // and everything has to come from one restaurant, which is returned
+    // along with the names of the items whose price (or an option's) isn't the one they were added at
+    async fn reprice_cart(&self, tx: &mut Transaction<'_, Postgres>, order_id: i32) -> Result<(i32, Vec<String>), Error> {
+        let items: Vec<CartItem> = sqlx::query(
+            "SELECT id, restaurant_id, food_id, bundle_id, name, price, discount_price
+            FROM item WHERE order_id = $1 ORDER BY id;",
+        )
+        .bind(order_id)
+        .map(|row: PgRow| CartItem {
+            id: row.get("id"),
+            restaurant_id: row.get("restaurant_id"),
+            food_id: row.get("food_id"),
+            bundle_id: row.get("bundle_id"),
+            name: row.get("name"),
+            price: row.get("price"),
+            discount_price: row.get("discount_price"),
+        })
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        let restaurant_id = match items.first() {
+            Some(item) => item.restaurant_id,
+            None => return Err(Error::empty_cart),
+        };
+        if items.iter().any(|item| item.restaurant_id != restaurant_id) {
+            return Err(Error::multiple_restaurants);
+        }
+
+        let components: Vec<(i32, Option<i32>, String)> = sqlx::query(
+            "SELECT item_component.item_id, item_component.food_id, item_component.name
+            FROM item_component
+            JOIN item ON item.id = item_component.item_id
+            WHERE item.order_id = $1;",
+        )
+        .bind(order_id)
+        .map(|row: PgRow| (row.get("item_id"), row.get("food_id"), row.get("name")))
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let food_ids: Vec<i32> = items
+            .iter()
+            .filter_map(|item| item.food_id)
+            .chain(components.iter().filter_map(|(_, food_id, _)| *food_id))
+            .collect();
+        // not locked: `take_stock` locks `food_stock` and then updates `food`, a lock taken here
+        // first would deadlock two checkouts of the same food
+        let mut foods = sqlx::query("SELECT * FROM food WHERE id = ANY($1);")
+            .bind(&food_ids)
+            .map(map_food)
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        apply_promotions_on(tx, &mut foods).await?;
+        apply_schedules_on(tx, &mut foods).await?;
+        let foods: HashMap<i32, Food> = foods.into_iter().map(|food| (food.id, food)).collect();
+        let orderable = |food_id: Option<i32>, name: &str| match food_id.and_then(|id| foods.get(&id)) {
+            Some(food) if food.orderable_now => Ok(food),
+            Some(food) => Err(Error::not_orderable(food.name.clone())),
+            None => Err(Error::not_orderable(name.to_string())),
+        };
+
+        let mut changed: Vec<String> = Vec::new();
+        for item in &items {
+            let (image, price, discount_price) = match (item.food_id, item.bundle_id) {
+                (Some(_), _) => {
+                    let food = orderable(item.food_id, &item.name)?;
+                    let discount_price = (food.effective_price < food.price).then_some(food.effective_price);
+                    (food.image.clone(), food.price, discount_price)
+                }
+                (None, Some(bundle_id)) => {
+                    let bundle = load_bundle(tx, restaurant_id, bundle_id).await?;
+                    if !bundle.orderable {
+                        return Err(Error::not_orderable(bundle.name));
+                    }
+                    for (_, food_id, name) in components.iter().filter(|(item_id, ..)| *item_id == item.id) {
+                        orderable(*food_id, name)?;
+                    }
+                    (bundle.image, bundle.price, None)
+                }
+                (None, None) => return Err(Error::not_orderable(item.name.clone())),
+            };
+            let name = item.food_id.and_then(|id| foods.get(&id)).map_or(&item.name, |food| &food.name);
+            if (price, discount_price) != (item.price, item.discount_price) {
+                changed.push(format!("`{name}`"));
+            }
+            sqlx::query(
+                "UPDATE item SET name = $1, image = $2, price = $3, discount_price = $4 WHERE id = $5;",
+            )
+            .bind(name)
+            .bind(image)
+            .bind(price)
+            .bind(discount_price)
+            .bind(item.id)
+            .execute(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        }
+
+        // options take their current price, one that was removed or switched off fails the checkout
+        let options: Vec<(i32, String, i32, Option<i32>, bool, String)> = sqlx::query(
+            "SELECT item_option.id, item_option.name, item_option.price_delta AS added_delta, food_option.price_delta,
+              COALESCE(food_option.available, FALSE) AS available, item.name AS item_name
+            FROM item_option
+            JOIN item ON item.id = item_option.item_id
+            LEFT JOIN food_option ON food_option.id = item_option.option_id
+            WHERE item.order_id = $1;",
+        )
+        .bind(order_id)
+        .map(|row: PgRow| {
+            (
+                row.get("id"),
+                row.get("name"),
+                row.get("added_delta"),
+                row.get("price_delta"),
+                row.get("available"),
+                row.get("item_name"),
+            )
+        })
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        for (option_id, name, added_delta, price_delta, available, item_name) in options {
+            let price_delta = match price_delta {
+                Some(price_delta) if available => price_delta,
+                _ => return Err(Error::invalid_options(format!("`{name}` is not available"))),
+            };
+            let item_name = format!("`{item_name}`");
+            if price_delta != added_delta && !changed.contains(&item_name) {
+                changed.push(item_name);
+            }
+            sqlx::query("UPDATE item_option SET price_delta = $1 WHERE id = $2;")
+                .bind(price_delta)
+                .bind(option_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(Error::database_query_error)?;
+        }
+        let below_zero = sqlx::query_scalar::<_, Option<String>>(
+            "UPDATE item
+            SET options_price = (
+              SELECT COALESCE(SUM(price_delta), 0)::INT FROM item_option WHERE item_option.item_id = item.id
+            )
+            WHERE order_id = $1
+            RETURNING CASE WHEN COALESCE(discount_price, price) + options_price < 0 THEN name END;",
+        )
+        .bind(order_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        match below_zero.into_iter().flatten().next() {
+            Some(name) => Err(Error::invalid_options(format!("the options take `{name}` below 0"))),
+            None => Ok((restaurant_id, changed)),
+        }
+    }
+
+    // the delivery location set on the cart is checked again against the zones of the cart's
+    // restaurant as it is now, with the repriced subtotal. returns the zone and its fee
+    // a cart without a location is picked up at the restaurant: no zone and no fee
+    async fn recheck_delivery(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: i32,
+        restaurant_id: i32,
+    ) -> Result<(Option<i32>, i32), Error> {
+        let (location, subtotal) = sqlx::query(
+            "SELECT delivery_location, (
+              SELECT COALESCE(SUM((COALESCE(discount_price, price) + options_price) * quantity), 0)::INT
+              FROM item WHERE order_id = $1
+            ) AS subtotal
+            FROM orders WHERE id = $1;",
+        )
+        .bind(order_id)
+        .map(|row: PgRow| (row.get::<Option<[f64; 2]>, _>("delivery_location"), row.get::<i32, _>("subtotal")))
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        let location = match location {
+            Some(location) => location,
+            None => return Ok((None, 0)),
+        };
+
+        let check = DeliveryCheck {
+            location,
+            subtotal: Some(subtotal),
+        };
+        let check = deliverability(tx, restaurant_id, check).await?;
+        let (zone_id, fee) = match (check.zone_id, check.fee) {
+            (Some(zone_id), Some(fee)) => (zone_id, fee),
+            _ => return Err(Error::not_deliverable),
+        };
+        if check.meets_minimum == Some(false) {
+            return Err(Error::below_minimum_order(check.minimum_order.unwrap_or_default()));
+        }
+        Ok((Some(zone_id), fee))
+    }
+
+    // turns the account's cart into a pending order, at the prices of the moment (see `reprice_cart`)
+    // and with the delivery fee of the zone the location falls in now (see `recheck_delivery`)
+    // when a price isn't the one the item was added at, the cart takes the new prices and stays a cart,
+    // checking out again accepts them
+    // foods with a daily stock must have enough left, otherwise nothing is taken and the cart stays
+    pub async fn checkout(&self, account_id: Uuid) -> Result<GetOrder, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let order_id = sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM orders WHERE account_id = $1 AND status = 'cart' LIMIT 1 FOR UPDATE;",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .ok_or(Error::empty_cart)?;
+
+        let has_items = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM item WHERE order_id = $1);",
+        )
+        .bind(order_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        if !has_items {
+            return Err(Error::empty_cart);
+        }
+
+        let (restaurant_id, changed) = self.reprice_cart(&mut tx, order_id).await?;
+        if !changed.is_empty() {
+            tx.commit().await.map_err(Error::database_query_error)?;
+            return Err(Error::prices_changed(changed.join(", ")));
+        }
+        let (zone_id, fee) = self.recheck_delivery(&mut tx, order_id, restaurant_id).await?;
+        let sold_out = take_stock(&mut tx, order_id).await?;
+
+        // the totals are kept on the order as they were charged
+        sqlx::query(
+            "UPDATE orders
+            SET status = 'pending', delivery_zone_id = $2, delivery_fee = $3,
+              total_price = (
+                SELECT COALESCE(SUM((price + options_price) * quantity), 0)::INT
+                FROM item WHERE order_id = $1
+              ) + $3,
+              total_discounted_price = (
+                SELECT COALESCE(SUM((COALESCE(discount_price, price) + options_price) * quantity), 0)::INT
+                FROM item WHERE order_id = $1
+              ) + $3
+            WHERE id = $1;",
+        )
+        .bind(order_id)
+        .bind(zone_id)
+        .bind(fee)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        record_status(
+            &mut *tx,
+            order_id,
+            OrderStatus::Cart,
+            OrderStatus::Pending,
+            StatusActor::customer,
+            Some(account_id),
+        )
+        .await?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        for restaurant_id in sold_out {
+            self.cache.invalidate_menu(restaurant_id);
+        }
+        self.get_customer_orders(account_id, order_id).await
+    }
+}
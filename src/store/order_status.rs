@@ -0,0 +1,185 @@
+use sqlx::{postgres::PgRow, PgExecutor, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::orders::{OrderStatus, OrderStatusChange, StatusActor, AUTO_COMPLETE_AFTER_MINUTES},
+};
+
+use super::{stock::give_back_stock, Store};
+
+fn map_status_change(row: PgRow) -> OrderStatusChange {
+    OrderStatusChange {
+        id: row.get("id"),
+        order_id: row.get("order_id"),
+        from_status: row.get("from_status"),
+        to_status: row.get("to_status"),
+        actor: row.get("actor"),
+        actor_id: row.get("actor_id"),
+        changed_at: row.get("changed_at"),
+    }
+}
+
+// adds an entry to the order's history, in the same transaction as the change itself
+pub(super) async fn record_status(
+    executor: impl PgExecutor<'_>,
+    order_id: i32,
+    from: OrderStatus,
+    to: OrderStatus,
+    actor: StatusActor,
+    actor_id: Option<Uuid>,
+) -> Result<OrderStatusChange, Error> {
+    sqlx::query(
+        "INSERT INTO order_status_history (order_id, from_status, to_status, actor, actor_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *;",
+    )
+    .bind(order_id)
+    .bind(from)
+    .bind(to)
+    .bind(actor)
+    .bind(actor_id)
+    .map(map_status_change)
+    .fetch_one(executor)
+    .await
+    .map_err(Error::database_query_error)
+}
+
+impl Store {
+    // the account runs every restaurant the order has items from
+    async fn is_order_restaurant(&self, account_id: Uuid, order_id: i32) -> Result<bool, Error> {
+        let restaurants = sqlx::query_scalar::<_, i32>(
+            "SELECT DISTINCT restaurant_id FROM item WHERE order_id = $1;",
+        )
+        .bind(order_id)
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+        if restaurants.is_empty() {
+            return Ok(false);
+        }
+        for restaurant_id in restaurants {
+            if !self
+                .verify_restaurant_modification_access(restaurant_id, &account_id)
+                .await?
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // the restaurant's moves win when the account is both the customer and the owner
+    // canceling gives the stock taken at checkout back, in the same transaction
+    pub async fn set_order_status(
+        &self,
+        account_id: Uuid,
+        order_id: i32,
+        status: OrderStatus,
+    ) -> Result<OrderStatusChange, Error> {
+        let is_owner = self.is_order_restaurant(account_id, order_id).await?;
+
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let (customer_id, current): (Uuid, OrderStatus) = sqlx::query(
+            "SELECT account_id, status FROM orders WHERE id = $1 FOR UPDATE;",
+        )
+        .bind(order_id)
+        .map(|row: PgRow| (row.get("account_id"), row.get("status")))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        let is_customer = customer_id == account_id;
+        if !is_owner && !is_customer {
+            return Err(Error::not_authorized);
+        }
+
+        let actor = if is_owner && current.can_change_to(status, StatusActor::owner) {
+            StatusActor::owner
+        } else if is_customer && current.can_change_to(status, StatusActor::customer) {
+            StatusActor::customer
+        } else {
+            return Err(Error::invalid_status_change(format!(
+                "{} to {} isn't allowed",
+                current._to_str(),
+                status._to_str()
+            )));
+        };
+
+        sqlx::query("UPDATE orders SET status = $1 WHERE id = $2;")
+            .bind(status)
+            .bind(order_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        let restocked = match status {
+            OrderStatus::Canceled => give_back_stock(&mut tx, order_id).await?,
+            _ => Default::default(),
+        };
+        let change = record_status(&mut *tx, order_id, current, status, actor, Some(account_id)).await?;
+        tx.commit().await.map_err(Error::database_query_error)?;
+        for restaurant_id in restocked {
+            self.cache.invalidate_menu(restaurant_id);
+        }
+        Ok(change)
+    }
+
+    // oldest first, for the customer and the restaurant
+    pub async fn get_order_status_history(
+        &self,
+        account_id: Uuid,
+        order_id: i32,
+    ) -> Result<Vec<OrderStatusChange>, Error> {
+        let customer_id = sqlx::query_scalar::<_, Uuid>("SELECT account_id FROM orders WHERE id = $1;")
+            .bind(order_id)
+            .fetch_one(&self.connection)
+            .await
+            .map_err(Error::database_query_error)?;
+        if customer_id != account_id && !self.is_order_restaurant(account_id, order_id).await? {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query(
+            "SELECT * FROM order_status_history
+            WHERE order_id = $1
+            ORDER BY changed_at, id;",
+        )
+        .bind(order_id)
+        .map(map_status_change)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(history) => Ok(history),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // completes the orders that went out for delivery `AUTO_COMPLETE_AFTER_MINUTES` ago
+    // called once a minute from main, returns how many orders were completed
+    pub async fn complete_delivered_orders(&self) -> Result<u64, Error> {
+        match sqlx::query(
+            "WITH due AS (
+              SELECT orders.id FROM orders
+              WHERE orders.status = 'out_for_delivery'
+                AND (
+                  SELECT MAX(changed_at) FROM order_status_history
+                  WHERE order_status_history.order_id = orders.id
+                    AND order_status_history.to_status = 'out_for_delivery'
+                ) < NOW() - make_interval(mins => $1)
+              FOR UPDATE SKIP LOCKED
+            ), completed AS (
+              UPDATE orders SET status = 'completed'
+              FROM due
+              WHERE orders.id = due.id
+              RETURNING orders.id
+            )
+            INSERT INTO order_status_history (order_id, from_status, to_status, actor)
+            SELECT id, 'out_for_delivery', 'completed', 'system' FROM completed;",
+        )
+        .bind(AUTO_COMPLETE_AFTER_MINUTES)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(res) => Ok(res.rows_affected()),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+}
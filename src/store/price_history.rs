@@ -0,0 +1,60 @@
+use sqlx::{postgres::PgRow, PgExecutor, Row};
+
+use crate::{error::Error, types::price_history::PriceChange};
+
+use super::Store;
+
+// adds the food's current price to its history, unless it's the same as the last entry
+// called after every write of `price`, in the same transaction
+pub(super) async fn record_price(executor: impl PgExecutor<'_>, food_id: i32) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO food_price_history (food_id, price)
+        SELECT food.id, food.price
+        FROM food
+        WHERE food.id = $1 AND NOT EXISTS (
+          SELECT 1 FROM (
+            SELECT price FROM food_price_history
+            WHERE food_id = $1
+            ORDER BY id DESC
+            LIMIT 1
+          ) last
+          WHERE last.price = food.price
+        );",
+    )
+    .bind(food_id)
+    .execute(executor)
+    .await
+    .map_err(Error::database_query_error)?;
+    Ok(())
+}
+
+impl Store {
+    // newest first
+    pub async fn get_price_history(
+        &self,
+        restaurant_id: i32,
+        food_id: i32,
+    ) -> Result<Vec<PriceChange>, Error> {
+        match sqlx::query(
+            "SELECT food_price_history.*
+            FROM food_price_history
+            JOIN food ON food.id = food_price_history.food_id
+            WHERE food_price_history.food_id = $1 AND food.restaurant_id = $2
+            ORDER BY food_price_history.id DESC;",
+        )
+        .bind(food_id)
+        .bind(restaurant_id)
+        .map(|row: PgRow| PriceChange {
+            id: row.get("id"),
+            food_id: row.get("food_id"),
+            price: row.get("price"),
+            changed_at: row.get("changed_at"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(history) => Ok(history),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+}
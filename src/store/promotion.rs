@@ -0,0 +1,249 @@
+use chrono::{Local, NaiveDateTime};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::{
+        food::Food,
+        promotion::{apply_promotions, NewPromotion, Promotion},
+    },
+};
+
+use super::{section::check_section, Store};
+
+fn map_promotion(row: PgRow) -> Promotion {
+    Promotion {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        name: row.get("name"),
+        kind: row.get("kind"),
+        value: row.get("value"),
+        food_id: row.get("food_id"),
+        tag_id: row.get("tag_id"),
+        section_id: row.get("section_id"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        days: row.get("days"),
+        window_from: row.get("window_from"),
+        window_to: row.get("window_to"),
+        active: row.get("active"),
+    }
+}
+
+// fills `effective_price` and `promotion_id` of foods from any restaurants
+// on a connection of its own, or on a transaction's (the cart prices the foods it reads)
+pub(super) async fn apply_promotions_on(connection: &mut PgConnection, foods: &mut [Food]) -> Result<(), Error> {
+    let now = Local::now().naive_local();
+    let mut restaurant_ids: Vec<i32> = foods.iter().map(|food| food.restaurant_id).collect();
+    restaurant_ids.sort_unstable();
+    restaurant_ids.dedup();
+
+    let promotions = sqlx::query(
+        "SELECT * FROM promotion
+        WHERE restaurant_id = ANY($1) AND active
+          AND (starts_at IS NULL OR starts_at <= $2)
+          AND (ends_at IS NULL OR ends_at > $2);",
+    )
+    .bind(restaurant_ids)
+    .bind(now)
+    .map(map_promotion)
+    .fetch_all(connection)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    apply_promotions(foods, &promotions, now);
+    Ok(())
+}
+
+impl Store {
+    pub async fn get_promotions(&self, restaurant_id: i32) -> Result<Vec<Promotion>, Error> {
+        match sqlx::query(
+            "SELECT * FROM promotion
+            WHERE restaurant_id = $1
+            ORDER BY starts_at NULLS FIRST, id;",
+        )
+        .bind(restaurant_id)
+        .map(map_promotion)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(promotions) => Ok(promotions),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn apply_promotions(&self, foods: &mut [Food]) -> Result<(), Error> {
+        let mut connection = self.connection.acquire().await.map_err(Error::database_query_error)?;
+        apply_promotions_on(&mut connection, foods).await
+    }
+
+    // when a promotion of the restaurant next starts or stops, so cached prices don't outlive it
+    pub async fn next_promotion_change(
+        &self,
+        restaurant_id: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<NaiveDateTime>, Error> {
+        let promotions = sqlx::query(
+            "SELECT * FROM promotion
+            WHERE restaurant_id = $1 AND active AND (ends_at IS NULL OR ends_at > $2);",
+        )
+        .bind(restaurant_id)
+        .bind(now)
+        .map(map_promotion)
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+        Ok(promotions
+            .iter()
+            .filter_map(|promotion| promotion.next_change_after(now))
+            .min())
+    }
+
+    // a food or section target has to belong to the restaurant
+    async fn check_promotion_target(
+        &self,
+        restaurant_id: i32,
+        promotion: &NewPromotion,
+    ) -> Result<(), Error> {
+        if let Some(food_id) = promotion.food_id {
+            let exists = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM food WHERE id = $1 AND restaurant_id = $2);",
+            )
+            .bind(food_id)
+            .bind(restaurant_id)
+            .fetch_one(&self.connection)
+            .await
+            .map_err(Error::database_query_error)?;
+            if !exists {
+                return Err(Error::invalid_field(format!(
+                    "food {food_id} doesn't belong to restaurant {restaurant_id}"
+                )));
+            }
+        }
+        check_section(&self.connection, restaurant_id, promotion.section_id).await
+    }
+
+    pub async fn add_promotion(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        promotion: NewPromotion,
+    ) -> Result<Promotion, Error> {
+        promotion.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        self.check_promotion_target(restaurant_id, &promotion).await?;
+
+        match sqlx::query(
+            "INSERT INTO promotion (
+              restaurant_id, name, kind, value, food_id, tag_id, section_id,
+              starts_at, ends_at, days, window_from, window_to, active
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *;",
+        )
+        .bind(restaurant_id)
+        .bind(promotion.name.trim())
+        .bind(promotion.kind)
+        .bind(promotion.value)
+        .bind(promotion.food_id)
+        .bind(promotion.tag_id)
+        .bind(promotion.section_id)
+        .bind(promotion.starts_at)
+        .bind(promotion.ends_at)
+        .bind(promotion.days)
+        .bind(promotion.window_from)
+        .bind(promotion.window_to)
+        .bind(promotion.active)
+        .map(map_promotion)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(promotion) => {
+                self.cache.invalidate_menu(restaurant_id);
+                Ok(promotion)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn update_promotion(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        promotion_id: i32,
+        promotion: NewPromotion,
+    ) -> Result<Promotion, Error> {
+        promotion.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        self.check_promotion_target(restaurant_id, &promotion).await?;
+
+        match sqlx::query(
+            "UPDATE promotion
+            SET name = $1, kind = $2, value = $3, food_id = $4, tag_id = $5, section_id = $6,
+              starts_at = $7, ends_at = $8, days = $9, window_from = $10, window_to = $11, active = $12
+            WHERE id = $13 AND restaurant_id = $14
+            RETURNING *;",
+        )
+        .bind(promotion.name.trim())
+        .bind(promotion.kind)
+        .bind(promotion.value)
+        .bind(promotion.food_id)
+        .bind(promotion.tag_id)
+        .bind(promotion.section_id)
+        .bind(promotion.starts_at)
+        .bind(promotion.ends_at)
+        .bind(promotion.days)
+        .bind(promotion.window_from)
+        .bind(promotion.window_to)
+        .bind(promotion.active)
+        .bind(promotion_id)
+        .bind(restaurant_id)
+        .map(map_promotion)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(promotion) => {
+                self.cache.invalidate_menu(restaurant_id);
+                Ok(promotion)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn delete_promotion(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        promotion_id: i32,
+    ) -> Result<bool, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query("DELETE FROM promotion WHERE id = $1 AND restaurant_id = $2;")
+            .bind(promotion_id)
+            .bind(restaurant_id)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(res) => {
+                self.cache.invalidate_menu(restaurant_id);
+                Ok(res.rows_affected() > 0)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+}
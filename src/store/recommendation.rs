@@ -0,0 +1,96 @@
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+use crate::{error::Error, types::recommendation::Recommendation};
+
+use super::{restaurant::map_restaurant, Store};
+
+impl Store {
+    // score = what the user ordered there + how much of its menu matches the tags they order
+    //       + favourites + their own rating, on top of a popularity score that new users get alone.
+    // restaurants the user rated 2 or less are never recommended.
+    // without `city` the city they ordered from the most (or their favourites' city) is used
+    pub async fn get_recommendations(
+        &self,
+        account_id: Uuid,
+        city: Option<String>,
+        limit: i32,
+    ) -> Result<Vec<Recommendation>, Error> {
+        match sqlx::query(
+            "WITH my_items AS (
+              SELECT item.restaurant_id, item.quantity, food.tag
+              FROM item
+              JOIN orders ON orders.id = item.order_id
+              JOIN food ON food.id = item.food_id
+              WHERE item.account_id = $1 AND orders.status NOT IN ('cart', 'canceled')
+            ),
+            my_tags AS (
+              SELECT tag, SUM(quantity) AS weight FROM my_items GROUP BY tag
+            ),
+            my_city AS (
+              SELECT COALESCE(
+                $2,
+                (SELECT restaurant.city FROM my_items
+                  JOIN restaurant ON restaurant.id = my_items.restaurant_id
+                  GROUP BY restaurant.city ORDER BY SUM(my_items.quantity) DESC LIMIT 1),
+                (SELECT restaurant.city FROM favorite_restaurant
+                  JOIN restaurant ON restaurant.id = favorite_restaurant.restaurant_id
+                  WHERE favorite_restaurant.account_id = $1
+                  GROUP BY restaurant.city ORDER BY COUNT(*) DESC LIMIT 1)
+              ) AS city
+            ),
+            scored AS (
+              SELECT restaurant.*,
+              COALESCE((SELECT AVG(comments.rating) FROM comments WHERE comments.restaurant_id = restaurant.id), 0)::FLOAT8 AS avg_rating,
+              (SELECT comments.rating FROM comments
+                WHERE comments.restaurant_id = restaurant.id AND comments.account_id = $1) AS my_rating,
+              COALESCE((SELECT SUM(quantity) FROM my_items WHERE my_items.restaurant_id = restaurant.id), 0) AS ordered,
+              COALESCE((SELECT SUM(my_tags.weight) FROM my_tags
+                WHERE my_tags.tag IN (SELECT food.tag FROM food WHERE food.restaurant_id = restaurant.id)), 0) AS tag_weight,
+              EXISTS (SELECT 1 FROM favorite_restaurant
+                WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $1) AS is_favorite,
+              (SELECT COUNT(*) FROM favorite_food JOIN food ON food.id = favorite_food.food_id
+                WHERE food.restaurant_id = restaurant.id AND favorite_food.account_id = $1) AS favorite_foods,
+              (SELECT COUNT(DISTINCT item.order_id) FROM item
+                JOIN orders ON orders.id = item.order_id
+                WHERE item.restaurant_id = restaurant.id AND orders.status NOT IN ('cart', 'canceled')
+                AND orders.created_at > NOW() - INTERVAL '30 days') AS recent_orders
+              FROM restaurant, my_city
+              WHERE my_city.city IS NULL OR restaurant.city = my_city.city
+            )
+            SELECT scored.id, scored.name, scored.distance, scored.tags, scored.image,
+            scored.address, scored.city, scored.location, scored.version, scored.is_favorite,
+            scored.avg_rating AS rating,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = scored.id ORDER BY tag_id) AS tag_ids,
+            (
+              3 * LN(1 + ordered)
+              + 2 * LN(1 + tag_weight)
+              + CASE WHEN is_favorite THEN 4 ELSE 0 END
+              + 2 * LN(1 + favorite_foods)
+              + 1.5 * COALESCE(my_rating - 3, 0)
+              + avg_rating / 5
+              + 0.5 * LN(1 + recent_orders)
+            )::FLOAT8 AS score
+            FROM scored
+            WHERE my_rating IS NULL OR my_rating > 2
+            ORDER BY score DESC, rating DESC, scored.id
+            LIMIT $3",
+        )
+        .bind(account_id)
+        .bind(city)
+        .bind(limit)
+        .map(|row: PgRow| Recommendation {
+            score: row.get("score"),
+            restaurant: map_restaurant(row),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(recommendations) => Ok(recommendations),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::database_query_error(e))
+            }
+        }
+    }
+}
@@ -4,8 +4,31 @@ use crate::{
 };
 use sqlx::postgres::PgRow;
 use sqlx::Row;
+use uuid::Uuid;
 
-use super::Store;
+use crate::utils::etag::IfMatch;
+
+use super::{tag::set_restaurant_tags, version::check_version, Store};
+
+// every query that builds a `Restaurant` selects
+// `ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids`
+// and `is_favorite`, which is NULL when the request isn't authenticated
+pub(crate) fn map_restaurant(row: PgRow) -> Restaurant {
+    Restaurant {
+        id: RestaurantId(row.get("id")),
+        name: row.get("name"),
+        rating: row.get("rating"),
+        distance: row.get("distance"),
+        tags: row.get("tags"),
+        tag_ids: Some(row.get("tag_ids")),
+        image: row.get("image"),
+        address: row.get("address"),
+        city: row.get("city"),
+        location: row.get("location"),
+        is_favorite: row.get("is_favorite"),
+        version: row.get("version"),
+    }
+}
 
 impl Store {
     pub async fn get_restaurants(
@@ -14,13 +37,21 @@ impl Store {
         limit: Option<i32>,
         // if we pass 0 as an offset, it will do the same
         offset: i32,
+        // the logged in user, used for `is_favorite`
+        account_id: Option<Uuid>,
     ) -> Result<Vec<Restaurant>, Error> {
         
         // println!("limit = {:?}, offset = {}", limit, offset);
         match sqlx::query(
             "SELECT 
-            restaurant.*, 
-            COALESCE(AVG(comments.rating), 0) AS average_rating
+            restaurant.id, restaurant.name, restaurant.distance, restaurant.tags, restaurant.image,
+            restaurant.address, restaurant.city, restaurant.location, restaurant.version,
+            COALESCE(AVG(comments.rating), 0) AS rating,
+            CASE WHEN $3::UUID IS NULL THEN NULL ELSE EXISTS (
+              SELECT 1 FROM favorite_restaurant
+              WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $3
+            ) END AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
             FROM restaurant
             LEFT JOIN comments 
             ON restaurant.id = comments.restaurant_id
@@ -29,17 +60,8 @@ impl Store {
         )
         .bind(limit)
         .bind(offset)
-        .map(|row: PgRow| Restaurant {
-            id: RestaurantId(row.get("id")),
-            name: row.get("name"),
-            rating: row.get("average_rating"),
-            distance: row.get("distance"),
-            tags: row.get("tags"),
-            image: row.get("image"),
-            address: row.get("address"),
-            city: row.get("city"),
-            location: row.get("location"),
-        })
+        .bind(account_id)
+        .map(map_restaurant)
         .fetch_all(&self.connection)
         .await
         {
@@ -50,107 +72,130 @@ impl Store {
             }
         }
     }
+
+    // with `tag_ids` the free text `tags` column is filled with the tag slugs
+    // an `image` starts the gallery as its cover
     pub async fn add_restaurant(&self, new_restaurant: NewRestaurant) -> Result<Restaurant, Error> {
-        match sqlx::query(
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        let restaurant_id = sqlx::query_scalar::<_, i32>(
             "INSERT INTO restaurant (name, rating, distance, tags, image, address, city, location)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, name, rating, distance, tags, image, address, city, location
+            RETURNING id
             ",
         )
         .bind(new_restaurant.name)
         .bind(new_restaurant.rating)
         .bind(new_restaurant.distance)
         .bind(new_restaurant.tags)
-        .bind(new_restaurant.image)
+        .bind(&new_restaurant.image)
         .bind(new_restaurant.address)
         .bind(new_restaurant.city)
         .bind(new_restaurant.location)
-        .map(|row| Restaurant {
-            id: RestaurantId(row.get("id")),
-            name: row.get("name"),
-            rating: row.get("rating"),
-            distance: row.get("distance"),
-            tags: row.get("tags"),
-            image: row.get("image"),
-            address: row.get("address"),
-            city: row.get("city"),
-            location: row.get("location"),
-        })
-        .fetch_one(&self.connection)
+        .fetch_one(&mut *tx)
         .await
-        {
-            Ok(restaurant) => Ok(restaurant),
-            Err(e) => Err(Error::database_query_error(e)),
+        .map_err(Error::database_query_error)?;
+
+        if !new_restaurant.image.is_empty() {
+            sqlx::query(
+                "INSERT INTO restaurant_image (restaurant_id, url, is_cover)
+                VALUES ($1, $2, TRUE);",
+            )
+            .bind(restaurant_id)
+            .bind(&new_restaurant.image)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
         }
+
+        if let Some(tag_ids) = new_restaurant.tag_ids {
+            set_restaurant_tags(&mut tx, restaurant_id, &tag_ids).await?;
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_lists();
+        self.get_single_restaurant(restaurant_id, None).await
     }
 
+    // `tag_ids: null` keeps the current tags, `tags` always follows the linked tags and the body's is ignored
+    // `image` is the gallery's cover and only changes through the gallery, the body's is ignored
     pub async fn update_restaurant(
         &self,
         restaurant: Restaurant,
         restaurant_id: i32,
+        if_match: &IfMatch,
     ) -> Result<Restaurant, Error> {
-        match sqlx::query(
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        check_version(&mut tx, "restaurant", restaurant_id, if_match).await?;
+
+        sqlx::query(
         "UPDATE restaurant 
-        SET name = $1, rating = $2, distance = $3, tags = $4, image = $5, address = $6, city = $7, location = $8
-        WHERE id = $9
-        RETURNING id, name, rating, distance, tags, image, address, city, location
+        SET name = $1, rating = $2, distance = $3, address = $4, city = $5, location = $6,
+        tags = ARRAY(
+          SELECT tag.slug FROM restaurant_tag
+          JOIN tag ON tag.id = restaurant_tag.tag_id
+          WHERE restaurant_tag.restaurant_id = restaurant.id
+          ORDER BY tag.slug
+        ),
+        version = version + 1
+        WHERE id = $7
+        RETURNING id
         ",
     )
     .bind(restaurant.name)
     .bind(restaurant.rating)
     .bind(restaurant.distance)
-    .bind(restaurant.tags)
-    .bind(restaurant.image)
     .bind(restaurant.address)
     .bind(restaurant.city)
     .bind(restaurant.location)
     .bind(restaurant_id)
-
-    .map(|row| Restaurant {
-        id: RestaurantId(row.get("id")),
-        name: row.get("name"),
-        rating: row.get("rating"),
-        distance: row.get("distance"),
-        tags: row.get("tags"),
-        image: row.get("image"),
-        address: row.get("address"),
-        city: row.get("city"),
-        location: row.get("location")
-    })
-    .fetch_one(&self.connection)
+    .fetch_one(&mut *tx)
     .await
-    {
-        Ok(restaurant) => Ok(restaurant),
-        Err(e) => Err(Error::database_query_error(e)),
-    }
+    .map_err(Error::database_query_error)?;
+
+        if let Some(tag_ids) = restaurant.tag_ids {
+            set_restaurant_tags(&mut tx, restaurant_id, &tag_ids).await?;
+        }
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_restaurant(restaurant_id);
+        self.get_single_restaurant(restaurant_id, None).await
     }
-    pub async fn delete_restaurant(&self, restaurant_id: i32) -> Result<bool, Error> {
+    pub async fn delete_restaurant(&self, restaurant_id: i32, if_match: &IfMatch) -> Result<bool, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        check_version(&mut tx, "restaurant", restaurant_id, if_match).await?;
+
         match sqlx::query("DELETE FROM restaurant WHERE id = $1")
             .bind(restaurant_id)
-            .execute(&self.connection)
+            .execute(&mut *tx)
             .await
         {
-            Ok(_) => Ok(true),
+            Ok(_) => {
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.invalidate_restaurant(restaurant_id);
+                Ok(true)
+            }
             Err(e) => Err(Error::database_query_error(e)),
         }
     }
-    pub async fn get_single_restaurant(&self, restaurant_id: i32) -> Result<Restaurant, Error> {
+    pub async fn get_single_restaurant(
+        &self,
+        restaurant_id: i32,
+        account_id: Option<Uuid>,
+    ) -> Result<Restaurant, Error> {
         match sqlx::query(
-            "SELECT * FROM restaurant
+            "SELECT restaurant.*,
+            CASE WHEN $2::UUID IS NULL THEN NULL ELSE EXISTS (
+              SELECT 1 FROM favorite_restaurant
+              WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $2
+            ) END AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
+            FROM restaurant
         WHERE id = $1",
         )
         .bind(restaurant_id)
-        .map(|row: PgRow| Restaurant {
-            id: RestaurantId(row.get("id")),
-            name: row.get("name"),
-            rating: row.get("rating"),
-            distance: row.get("distance"),
-            tags: row.get("tags"),
-            image: row.get("image"),
-            address: row.get("address"),
-            city: row.get("city"),
-            location: row.get("location"),
-        })
+        .bind(account_id)
+        .map(map_restaurant)
         .fetch_one(&self.connection)
         .await
         {
@@ -159,26 +204,6 @@ impl Store {
         }
     }
 
-    pub async fn insert_file_to_restaurant(
-        &self,
-        url: &str,
-        restaurant_id: i32,
-    ) -> Result<bool, Error> {
-        match sqlx::query(
-            "UPDATE restaurant 
-            SET image = $1
-            WHERE id = $2",
-        )
-        .bind(url)
-        .bind(restaurant_id)
-        .execute(&self.connection)
-        .await
-        {
-            Ok(_) => Ok(true),
-            Err(e) => Err(Error::database_query_error(e)),
-        }
-    }
-
     pub async fn _get_restaurant_pfp_image(&self, restaurant_id: i32) -> Result<String, Error> {
         match sqlx::query(
             "SELECT image from restaurant
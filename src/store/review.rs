@@ -0,0 +1,162 @@
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::review::{FoodReview, NewFoodReview},
+};
+
+use super::Store;
+
+fn map_food_review(row: PgRow) -> FoodReview {
+    FoodReview {
+        id: row.get("id"),
+        food_id: row.get("food_id"),
+        account_id: row.get("account_id"),
+        name: row.get("name"),
+        rating: row.get("rating"),
+        text: row.get("text"),
+        created_on: row.get("created_on"),
+    }
+}
+
+// `food.rating` and `food.rating_count` are kept next to the food so every `Food` has them
+// they are derived data, the food's version stays the same
+async fn update_food_rating(
+    tx: &mut Transaction<'_, Postgres>,
+    food_id: i32,
+) -> Result<(), Error> {
+    sqlx::query(
+        "UPDATE food SET
+          rating = COALESCE((SELECT AVG(rating) FROM food_review WHERE food_id = $1), 0)::FLOAT8,
+          rating_count = (SELECT COUNT(*) FROM food_review WHERE food_id = $1)::INT
+        WHERE id = $1;",
+    )
+    .bind(food_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+    Ok(())
+}
+
+impl Store {
+    pub async fn get_food_reviews(
+        &self,
+        restaurant_id: i32,
+        food_id: i32,
+    ) -> Result<Vec<FoodReview>, Error> {
+        match sqlx::query(
+            "SELECT food_review.*, account.name
+            FROM food_review
+            JOIN food ON food.id = food_review.food_id
+            JOIN account ON account.id = food_review.account_id
+            WHERE food_review.food_id = $1 AND food.restaurant_id = $2
+            ORDER BY food_review.created_on DESC, food_review.id DESC;",
+        )
+        .bind(food_id)
+        .bind(restaurant_id)
+        .map(map_food_review)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(reviews) => Ok(reviews),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // posting again replaces the account's review of the food
+    pub async fn add_food_review(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        food_id: i32,
+        review: NewFoodReview,
+    ) -> Result<FoodReview, Error> {
+        review.validate()?;
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        let purchased = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (
+              SELECT 1 FROM item
+              JOIN orders ON orders.id = item.order_id
+              WHERE item.account_id = $1 AND item.restaurant_id = $3
+              AND (item.food_id = $2 OR EXISTS (
+                SELECT 1 FROM item_component
+                WHERE item_component.item_id = item.id AND item_component.food_id = $2
+              ))
+              AND orders.status = 'completed'
+            );",
+        )
+        .bind(account_id)
+        .bind(food_id)
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        if !purchased {
+            return Err(Error::not_purchased);
+        }
+
+        let review_id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO food_review (food_id, account_id, rating, text)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (food_id, account_id)
+            DO UPDATE SET rating = EXCLUDED.rating, text = EXCLUDED.text, created_on = NOW()
+            RETURNING id;",
+        )
+        .bind(food_id)
+        .bind(account_id)
+        .bind(review.rating)
+        .bind(review.text.trim())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+        update_food_rating(&mut tx, food_id).await?;
+
+        let review = sqlx::query(
+            "SELECT food_review.*, account.name
+            FROM food_review
+            JOIN account ON account.id = food_review.account_id
+            WHERE food_review.id = $1;",
+        )
+        .bind(review_id)
+        .map(map_food_review)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(review)
+    }
+
+    // an account can only delete its own review
+    pub async fn delete_food_review(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        food_id: i32,
+    ) -> Result<bool, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let deleted = sqlx::query(
+            "DELETE FROM food_review
+            USING food
+            WHERE food_review.food_id = $1 AND food_review.account_id = $2
+            AND food.id = food_review.food_id AND food.restaurant_id = $3;",
+        )
+        .bind(food_id)
+        .bind(account_id)
+        .bind(restaurant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .rows_affected()
+            > 0;
+        if deleted {
+            update_food_rating(&mut tx, food_id).await?;
+        }
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(deleted)
+    }
+}
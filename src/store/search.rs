@@ -1,33 +1,49 @@
 use crate::{
     error::Error,
-    types::restaurant::{Restaurant, RestaurantId},
+    types::{
+        dietary::DietaryFilter,
+        food_search::{FoodSearch, FoodSearchResult, FoodSort, RestaurantSummary},
+        restaurant::Restaurant,
+        tag::slugify,
+    },
 };
-use sqlx::postgres::PgRow;
-use sqlx::Row;
+use chrono::Local;
+use sqlx::{postgres::PgRow, Row};
 use urlencoding::decode;
+use uuid::Uuid;
 
-use super::Store;
+use super::{food::map_food, restaurant::map_restaurant, Store};
+
+// `max_price` and `sort=price` need `effective_price`, which is only known after promotions
+// are applied, so those searches read every match and refuse when there are more than this
+const FOOD_SEARCH_CANDIDATES: i64 = 500;
+
+// `q` is matched literally, not as a LIKE pattern
+fn escape_like(q: &str) -> String {
+    q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
 
 impl Store {
-    pub async fn search_by_city(&self, city: String) -> Result<Vec<Restaurant>, Error> {
+    pub async fn search_by_city(
+        &self,
+        city: String,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<Restaurant>, Error> {
         let decoded_city = decode(&city).unwrap().into_owned();
         match sqlx::query(
-            "SELECT * from restaurant
+            "SELECT restaurant.*,
+            CASE WHEN $2::UUID IS NULL THEN NULL ELSE EXISTS (
+              SELECT 1 FROM favorite_restaurant
+              WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $2
+            ) END AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
+            FROM restaurant
         WHERE city = $1
         ORDER BY rating DESC",
         )
         .bind(decoded_city)
-        .map(|row: PgRow| Restaurant {
-            id: RestaurantId(row.get("id")),
-            name: row.get("name"),
-            rating: row.get("rating"),
-            distance: row.get("distance"),
-            tags: row.get("tags"),
-            image: row.get("image"),
-            address: row.get("address"),
-            city: row.get("city"),
-            location: row.get("location"),
-        })
+        .bind(account_id)
+        .map(map_restaurant)
         .fetch_all(&self.connection)
         .await
         {
@@ -36,26 +52,39 @@ impl Store {
         }
     }
 
-    pub async fn search_by_tag(&self, tag: String, city: String) -> Result<Vec<Restaurant>, Error> {
+    // `tag` can be a managed tag (slug, persian or english label) or a legacy free text tag
+    pub async fn search_by_tag(
+        &self,
+        tag: String,
+        city: String,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<Restaurant>, Error> {
         let decoded_tag = decode(&tag).unwrap().into_owned();
         match sqlx::query(
-            "SELECT * from restaurant
-        WHERE $1 = ANY(tags) AND city = $2
+            "SELECT restaurant.*,
+            CASE WHEN $4::UUID IS NULL THEN NULL ELSE EXISTS (
+              SELECT 1 FROM favorite_restaurant
+              WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $4
+            ) END AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
+            FROM restaurant
+        WHERE city = $3
+        AND (
+          EXISTS (
+            SELECT 1 FROM restaurant_tag
+            JOIN tag ON tag.id = restaurant_tag.tag_id
+            WHERE restaurant_tag.restaurant_id = restaurant.id
+            AND (tag.slug = $2 OR tag.label_fa = $1 OR LOWER(tag.label_en) = LOWER($1))
+          )
+          OR $1 = ANY(tags)
+        )
         ORDER BY rating DESC",
         )
-        .bind(decoded_tag)
+        .bind(&decoded_tag)
+        .bind(slugify(&decoded_tag))
         .bind(city)
-        .map(|row: PgRow| Restaurant {
-            id: RestaurantId(row.get("id")),
-            name: row.get("name"),
-            rating: row.get("rating"),
-            distance: row.get("distance"),
-            tags: row.get("tags"),
-            image: row.get("image"),
-            address: row.get("address"),
-            city: row.get("city"),
-            location: row.get("location"),
-        })
+        .bind(account_id)
+        .map(map_restaurant)
         .fetch_all(&self.connection)
         .await
         {
@@ -63,4 +92,142 @@ impl Store {
             Err(e) => Err(Error::database_query_error(e)),
         }
     }
+
+    // restaurants with at least one available food that passes the filter ("has vegan options")
+    pub async fn search_by_dietary(
+        &self,
+        filter: &DietaryFilter,
+        city: Option<String>,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<Restaurant>, Error> {
+        match sqlx::query(
+            "SELECT restaurant.*,
+            CASE WHEN $5::UUID IS NULL THEN NULL ELSE EXISTS (
+              SELECT 1 FROM favorite_restaurant
+              WHERE favorite_restaurant.restaurant_id = restaurant.id AND favorite_restaurant.account_id = $5
+            ) END AS is_favorite,
+            ARRAY(SELECT tag_id FROM restaurant_tag WHERE restaurant_tag.restaurant_id = restaurant.id ORDER BY tag_id) AS tag_ids
+            FROM restaurant
+        WHERE ($4::TEXT IS NULL OR city = $4)
+        AND EXISTS (
+          SELECT 1 FROM food
+          WHERE food.restaurant_id = restaurant.id AND food.available
+          AND (cardinality($1::TEXT[]) = 0 OR NOT food.allergens && $1)
+          AND food.diets @> $2
+          AND ($3::INT IS NULL OR food.spicy_level <= $3)
+        )
+        ORDER BY rating DESC",
+        )
+        .bind(&filter.exclude_allergens)
+        .bind(&filter.diets)
+        .bind(filter.max_spicy)
+        .bind(city)
+        .bind(account_id)
+        .map(map_restaurant)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(restaurant) => Ok(restaurant),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // dishes of every restaurant whose name, tag or an ingredient contains `q`
+    pub async fn search_food(&self, search: &FoodSearch) -> Result<Vec<FoodSearchResult>, Error> {
+        // relevance and rating don't depend on promotions, the database pages those
+        let paged = search.max_price.is_none() && search.sort != FoodSort::Price;
+        let (limit, offset) = match paged {
+            true => (search.limit as i64, search.offset as i64),
+            false => (FOOD_SEARCH_CANDIDATES + 1, 0),
+        };
+        let now = Local::now().naive_local();
+        let rows = sqlx::query(
+            "SELECT * FROM (
+              SELECT food.*,
+                restaurant.name AS restaurant_name, restaurant.city AS restaurant_city,
+                restaurant.image AS restaurant_image,
+                COALESCE((SELECT AVG(comments.rating) FROM comments WHERE comments.restaurant_id = restaurant.id), 0)::FLOAT8 AS restaurant_rating,
+                (CASE
+                  WHEN LOWER(food.name) = LOWER($2) THEN 4
+                  WHEN food.name ILIKE $1 || '%' THEN 3
+                  WHEN food.name ILIKE '%' || $1 || '%' THEN 2
+                  ELSE 0
+                END
+                + CASE WHEN food.tag ILIKE '%' || $1 || '%' THEN 1.5 ELSE 0 END
+                + CASE WHEN EXISTS (
+                    SELECT 1 FROM unnest(food.ingredient) AS ingredient WHERE ingredient ILIKE '%' || $1 || '%'
+                  ) THEN 1 ELSE 0 END)::FLOAT8 AS relevance
+              FROM food
+              JOIN restaurant ON restaurant.id = food.restaurant_id
+              WHERE ($3::TEXT IS NULL OR restaurant.city = $3)
+              AND (NOT $4 OR food.available)
+              -- promotions only lower prices, without one running the price is final
+              AND ($7::INT IS NULL OR food.price <= $7 OR EXISTS (
+                SELECT 1 FROM promotion
+                WHERE promotion.restaurant_id = food.restaurant_id AND promotion.active
+                AND (promotion.ends_at IS NULL OR promotion.ends_at > $8)
+              ))
+            ) matches
+            WHERE relevance > 0
+            ORDER BY CASE WHEN $9 THEN restaurant_rating END DESC, relevance DESC, restaurant_rating DESC, id
+            LIMIT $5 OFFSET $6;",
+        )
+        .bind(escape_like(&search.q))
+        .bind(&search.q)
+        .bind(&search.city)
+        .bind(search.available_only)
+        .bind(limit)
+        .bind(offset)
+        .bind(search.max_price)
+        .bind(now)
+        .bind(search.sort == FoodSort::Rating)
+        .map(|row: PgRow| {
+            let restaurant = RestaurantSummary {
+                id: row.get("restaurant_id"),
+                name: row.get("restaurant_name"),
+                city: row.get("restaurant_city"),
+                image: row.get("restaurant_image"),
+                rating: row.get("restaurant_rating"),
+            };
+            let relevance = row.get("relevance");
+            (map_food(row), restaurant, relevance)
+        })
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+        if !paged && rows.len() as i64 > FOOD_SEARCH_CANDIDATES {
+            return Err(Error::too_many_matches);
+        }
+
+        let (mut foods, rest): (Vec<_>, Vec<_>) = rows
+            .into_iter()
+            .map(|(food, restaurant, relevance)| (food, (restaurant, relevance)))
+            .unzip();
+        self.apply_promotions(&mut foods).await?;
+        self.apply_schedules(&mut foods).await?;
+
+        let mut results: Vec<FoodSearchResult> = foods
+            .into_iter()
+            .zip(rest)
+            .map(|(food, (restaurant, relevance))| FoodSearchResult {
+                food,
+                restaurant,
+                relevance,
+            })
+            .filter(|result| {
+                search
+                    .max_price
+                    .is_none_or(|max_price| result.food.effective_price <= max_price)
+            })
+            .collect();
+        if paged {
+            return Ok(results);
+        }
+        FoodSearchResult::sort(&mut results, search.sort);
+        Ok(results
+            .into_iter()
+            .skip(search.offset as usize)
+            .take(search.limit as usize)
+            .collect())
+    }
 }
@@ -0,0 +1,263 @@
+use sqlx::{postgres::PgRow, PgExecutor, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::{
+        food::Food,
+        section::{MenuSection, NewMenuSection},
+    },
+};
+
+use super::{food::map_food, Store};
+
+fn map_menu_section(row: PgRow) -> MenuSection {
+    MenuSection {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        name: row.get("name"),
+        sort_order: row.get("sort_order"),
+        available_from: row.get("available_from"),
+        available_to: row.get("available_to"),
+    }
+}
+
+// a food can only be put in a section of its own restaurant
+pub(super) async fn check_section(
+    executor: impl PgExecutor<'_>,
+    restaurant_id: i32,
+    section_id: Option<i32>,
+) -> Result<(), Error> {
+    let Some(section_id) = section_id else {
+        return Ok(());
+    };
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM menu_section WHERE id = $1 AND restaurant_id = $2);",
+    )
+    .bind(section_id)
+    .bind(restaurant_id)
+    .fetch_one(executor)
+    .await
+    .map_err(Error::database_query_error)?;
+    if exists {
+        Ok(())
+    } else {
+        Err(Error::invalid_field(format!(
+            "section {section_id} doesn't belong to restaurant {restaurant_id}"
+        )))
+    }
+}
+
+impl Store {
+    pub async fn get_menu_sections(&self, restaurant_id: i32) -> Result<Vec<MenuSection>, Error> {
+        match sqlx::query(
+            "SELECT * FROM menu_section
+            WHERE restaurant_id = $1
+            ORDER BY sort_order, id;",
+        )
+        .bind(restaurant_id)
+        .map(map_menu_section)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(sections) => Ok(sections),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // new sections go to the end of the menu
+    pub async fn add_menu_section(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        section: NewMenuSection,
+    ) -> Result<MenuSection, Error> {
+        section.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query(
+            "INSERT INTO menu_section (restaurant_id, name, sort_order, available_from, available_to)
+            VALUES ($1, $2, (
+              SELECT COALESCE(MAX(sort_order) + 1, 0) FROM menu_section WHERE restaurant_id = $1
+            ), $3, $4)
+            RETURNING *;",
+        )
+        .bind(restaurant_id)
+        .bind(section.name.trim())
+        .bind(section.available_from)
+        .bind(section.available_to)
+        .map(map_menu_section)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(section) => {
+                self.cache.invalidate_menu(restaurant_id);
+                Ok(section)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn update_menu_section(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        section_id: i32,
+        section: NewMenuSection,
+    ) -> Result<MenuSection, Error> {
+        section.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query(
+            "UPDATE menu_section
+            SET name = $1, available_from = $2, available_to = $3
+            WHERE id = $4 AND restaurant_id = $5
+            RETURNING *;",
+        )
+        .bind(section.name.trim())
+        .bind(section.available_from)
+        .bind(section.available_to)
+        .bind(section_id)
+        .bind(restaurant_id)
+        .map(map_menu_section)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(section) => {
+                self.cache.invalidate_menu(restaurant_id);
+                Ok(section)
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // the foods of the section stay on the menu, without a section
+    pub async fn delete_menu_section(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        section_id: i32,
+    ) -> Result<bool, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        sqlx::query(
+            "UPDATE food SET section_id = NULL, version = version + 1
+            WHERE section_id = $1 AND restaurant_id = $2;",
+        )
+        .bind(section_id)
+        .bind(restaurant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        sqlx::query("DELETE FROM menu_section WHERE id = $1 AND restaurant_id = $2;")
+            .bind(section_id)
+            .bind(restaurant_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(true)
+    }
+
+    // `section_ids` is the new order, sections that are left out keep their relative order after them
+    pub async fn reorder_menu_sections(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        section_ids: Vec<i32>,
+    ) -> Result<Vec<MenuSection>, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query(
+            "UPDATE menu_section
+            SET sort_order = COALESCE(array_position($1, id) - 1, cardinality($1) + sort_order)
+            WHERE restaurant_id = $2;",
+        )
+        .bind(section_ids)
+        .bind(restaurant_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => {
+                self.cache.invalidate_menu(restaurant_id);
+                self.get_menu_sections(restaurant_id).await
+            }
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // same rules as `reorder_menu_sections`, for the foods of one section
+    // `sort_order` is part of the food, so the foods that move get a new version
+    pub async fn reorder_section_foods(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        section_id: i32,
+        food_ids: Vec<i32>,
+    ) -> Result<Vec<Food>, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        check_section(&mut *tx, restaurant_id, Some(section_id)).await?;
+
+        sqlx::query(
+            "UPDATE food
+            SET sort_order = ordered.sort_order, version = food.version + 1
+            FROM (
+              SELECT id, COALESCE(array_position($1, id) - 1, cardinality($1) + sort_order) AS sort_order
+              FROM food
+              WHERE restaurant_id = $2 AND section_id = $3
+            ) ordered
+            WHERE food.id = ordered.id AND food.sort_order <> ordered.sort_order;",
+        )
+        .bind(food_ids)
+        .bind(restaurant_id)
+        .bind(section_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let mut foods = sqlx::query(
+            "SELECT * FROM food
+            WHERE restaurant_id = $1 AND section_id = $2
+            ORDER BY sort_order, id;",
+        )
+        .bind(restaurant_id)
+        .bind(section_id)
+        .map(map_food)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        self.apply_promotions(&mut foods).await?;
+        self.apply_schedules(&mut foods).await?;
+        Ok(foods)
+    }
+}
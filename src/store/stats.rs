@@ -0,0 +1,206 @@
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::stats::{RatingPoint, RatingStats, SalesPoint, SalesStats, StatsRange, TopFood},
+};
+
+use super::Store;
+
+// every bucket between `from` and `to`, so days without sales show up as zeros
+// $2 = from, $3 = to, $4 = bucket unit
+const PERIODS: &str = "periods AS (
+  SELECT generate_series(date_trunc($4, $2::TIMESTAMP), $3::TIMESTAMP, ('1 ' || $4)::INTERVAL)::DATE AS period
+)";
+
+impl Store {
+    pub async fn get_sales_stats(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        range: StatsRange,
+    ) -> Result<SalesStats, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+
+        // an order is counted for the restaurant its items belong to
+        let series = match sqlx::query(&format!(
+            "WITH restaurant_orders AS (
+              SELECT orders.id, orders.status, date_trunc($4, orders.created_at)::DATE AS period,
+              SUM(item.quantity * (COALESCE(item.discount_price, item.price) + item.options_price))::BIGINT AS total
+              FROM orders
+              JOIN item ON item.order_id = orders.id
+              WHERE item.restaurant_id = $1
+              AND orders.status <> 'cart'
+              AND orders.created_at >= $2 AND orders.created_at < $3 + 1
+              GROUP BY orders.id
+            ),
+            {PERIODS}
+            SELECT periods.period,
+            COUNT(restaurant_orders.id) FILTER (WHERE restaurant_orders.status <> 'canceled') AS orders,
+            COUNT(restaurant_orders.id) FILTER (WHERE restaurant_orders.status = 'canceled') AS canceled_orders,
+            COALESCE(SUM(restaurant_orders.total) FILTER (WHERE restaurant_orders.status <> 'canceled'), 0)::BIGINT AS revenue
+            FROM periods
+            LEFT JOIN restaurant_orders ON restaurant_orders.period = periods.period
+            GROUP BY periods.period
+            ORDER BY periods.period"
+        ))
+        .bind(restaurant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.bucket.unit())
+        .map(|row: PgRow| SalesPoint {
+            period: row.get("period"),
+            orders: row.get("orders"),
+            canceled_orders: row.get("canceled_orders"),
+            revenue: row.get("revenue"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(series) => series,
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                return Err(Error::database_query_error(e));
+            }
+        };
+
+        let orders: i64 = series.iter().map(|p| p.orders).sum();
+        let canceled_orders: i64 = series.iter().map(|p| p.canceled_orders).sum();
+        let revenue: i64 = series.iter().map(|p| p.revenue).sum();
+        let average_basket = match orders {
+            0 => 0.0,
+            n => revenue as f64 / n as f64,
+        };
+        let cancellation_rate = match orders + canceled_orders {
+            0 => 0.0,
+            n => canceled_orders as f64 / n as f64,
+        };
+
+        Ok(SalesStats {
+            range,
+            orders,
+            canceled_orders,
+            revenue,
+            average_basket,
+            cancellation_rate,
+            series,
+        })
+    }
+
+    pub async fn get_top_foods(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        range: StatsRange,
+        limit: i32,
+    ) -> Result<Vec<TopFood>, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+
+        // `item.name` is the name at the time of the order, the latest one is shown
+        // bundles aren't foods, only what was ordered on its own is ranked
+        match sqlx::query(
+            "SELECT item.food_id,
+            (ARRAY_AGG(item.name ORDER BY orders.created_at DESC))[1] AS name,
+            SUM(item.quantity)::BIGINT AS quantity,
+            COUNT(DISTINCT item.order_id) AS orders,
+            SUM(item.quantity * (COALESCE(item.discount_price, item.price) + item.options_price))::BIGINT AS revenue
+            FROM item
+            JOIN orders ON orders.id = item.order_id
+            WHERE item.restaurant_id = $1 AND item.food_id IS NOT NULL
+            AND orders.status NOT IN ('cart', 'canceled')
+            AND orders.created_at >= $2 AND orders.created_at < $3 + 1
+            GROUP BY item.food_id
+            ORDER BY quantity DESC, revenue DESC, item.food_id
+            LIMIT $4",
+        )
+        .bind(restaurant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(limit)
+        .map(|row: PgRow| TopFood {
+            food_id: row.get("food_id"),
+            name: row.get("name"),
+            quantity: row.get("quantity"),
+            orders: row.get("orders"),
+            revenue: row.get("revenue"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(foods) => Ok(foods),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn get_rating_stats(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        range: StatsRange,
+    ) -> Result<RatingStats, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+
+        let series = match sqlx::query(&format!(
+            "WITH {PERIODS}
+            SELECT periods.period,
+            COUNT(comments.id) AS comments,
+            AVG(comments.rating)::FLOAT8 AS average_rating
+            FROM periods
+            LEFT JOIN comments ON comments.restaurant_id = $1
+            AND date_trunc($4, comments.created_on)::DATE = periods.period
+            AND comments.created_on >= $2 AND comments.created_on < $3 + 1
+            GROUP BY periods.period
+            ORDER BY periods.period"
+        ))
+        .bind(restaurant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.bucket.unit())
+        .map(|row: PgRow| RatingPoint {
+            period: row.get("period"),
+            comments: row.get("comments"),
+            average_rating: row.get("average_rating"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(series) => series,
+            Err(e) => return Err(Error::database_query_error(e)),
+        };
+
+        let comments: i64 = series.iter().map(|p| p.comments).sum();
+        let average_rating = match comments {
+            0 => None,
+            n => Some(
+                series
+                    .iter()
+                    .filter_map(|p| p.average_rating.map(|avg| avg * p.comments as f64))
+                    .sum::<f64>()
+                    / n as f64,
+            ),
+        };
+
+        Ok(RatingStats {
+            range,
+            comments,
+            average_rating,
+            series,
+        })
+    }
+}
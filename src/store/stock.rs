@@ -0,0 +1,343 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::stock::{FoodStock, NewFoodStock},
+};
+
+use super::Store;
+
+fn map_food_stock(row: PgRow) -> FoodStock {
+    FoodStock {
+        food_id: row.get("food_id"),
+        name: row.get("name"),
+        available: row.get("available"),
+        daily_quantity: row.get("daily_quantity"),
+        remaining: row.get("remaining"),
+        reset_time: row.get("reset_time"),
+        last_reset: row.get("last_reset"),
+        sold_out: row.get("sold_out"),
+    }
+}
+
+// how many of every food the order holds, in food id order
+// the foods of a bundle count like foods ordered on their own
+async fn ordered_foods(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: i32,
+) -> Result<Vec<(i32, String, i32)>, Error> {
+    sqlx::query(
+        "SELECT ordered.food_id, food.name, SUM(ordered.quantity)::INT AS quantity
+        FROM (
+          SELECT food_id, quantity FROM item
+          WHERE order_id = $1 AND food_id IS NOT NULL
+          UNION ALL
+          SELECT item_component.food_id, item_component.quantity * item.quantity
+          FROM item_component
+          JOIN item ON item.id = item_component.item_id
+          WHERE item.order_id = $1 AND item_component.food_id IS NOT NULL
+        ) ordered
+        JOIN food ON food.id = ordered.food_id
+        GROUP BY ordered.food_id, food.name
+        ORDER BY ordered.food_id;",
+    )
+    .bind(order_id)
+    .map(|row: PgRow| {
+        (
+            row.get::<i32, _>("food_id"),
+            row.get::<String, _>("name"),
+            row.get::<i32, _>("quantity"),
+        )
+    })
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)
+}
+
+// takes the quantities of an order from the stock of its foods, foods without stock are skipped
+// the stock rows stay locked until the transaction ends, so concurrent checkouts wait for each other
+// and always see what the other one left. foods are locked in id order so two carts can't deadlock
+// returns the restaurants whose menu changed because a food sold out
+pub(super) async fn take_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: i32,
+) -> Result<HashSet<i32>, Error> {
+    let ordered = ordered_foods(tx, order_id).await?;
+    let mut sold_out = HashSet::new();
+    for (food_id, name, quantity) in ordered {
+        let remaining = sqlx::query_scalar::<_, i32>(
+            "SELECT remaining FROM food_stock WHERE food_id = $1 FOR UPDATE;",
+        )
+        .bind(food_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let Some(remaining) = remaining else {
+            continue;
+        };
+        if remaining < quantity {
+            return Err(Error::out_of_stock(name));
+        }
+
+        sqlx::query(
+            "UPDATE food_stock SET remaining = remaining - $2, sold_out = remaining - $2 = 0
+            WHERE food_id = $1;",
+        )
+        .bind(food_id)
+        .bind(quantity)
+        .execute(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        if remaining == quantity {
+            let restaurant_id = sqlx::query_scalar::<_, i32>(
+                "UPDATE food SET available = FALSE, version = version + 1
+                WHERE id = $1
+                RETURNING restaurant_id;",
+            )
+            .bind(food_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+            sold_out.insert(restaurant_id);
+        }
+    }
+    Ok(sold_out)
+}
+
+// puts back what `take_stock` took for a canceled order, a food it sold out is available again
+// stock reset or set by hand since the checkout is left alone, the order's share is already gone from it
+// returns the restaurants whose menu changed
+pub(super) async fn give_back_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: i32,
+) -> Result<HashSet<i32>, Error> {
+    let checked_out = sqlx::query_scalar::<_, Option<NaiveDateTime>>(
+        "SELECT MAX(changed_at) FROM order_status_history
+        WHERE order_id = $1 AND from_status = 'cart';",
+    )
+    .bind(order_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+    let Some(checked_out) = checked_out else {
+        return Ok(HashSet::new());
+    };
+
+    let ordered = ordered_foods(tx, order_id).await?;
+    let mut restocked = HashSet::new();
+    for (food_id, _, quantity) in ordered {
+        let was_sold_out = sqlx::query_scalar::<_, bool>(
+            "UPDATE food_stock SET remaining = remaining + $2, sold_out = FALSE
+            FROM (SELECT sold_out FROM food_stock WHERE food_id = $1 FOR UPDATE) old
+            WHERE food_stock.food_id = $1 AND food_stock.last_reset <= $3
+            RETURNING old.sold_out;",
+        )
+        .bind(food_id)
+        .bind(quantity)
+        .bind(checked_out)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        if was_sold_out == Some(true) {
+            let restaurant_id = sqlx::query_scalar::<_, i32>(
+                "UPDATE food SET available = TRUE, version = version + 1
+                WHERE id = $1
+                RETURNING restaurant_id;",
+            )
+            .bind(food_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(Error::database_query_error)?;
+            restocked.insert(restaurant_id);
+        }
+    }
+    Ok(restocked)
+}
+
+impl Store {
+    pub async fn get_restaurant_stock(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+    ) -> Result<Vec<FoodStock>, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        match sqlx::query(
+            "SELECT food_stock.*, food.name, food.available
+            FROM food_stock
+            JOIN food ON food.id = food_stock.food_id
+            WHERE food.restaurant_id = $1
+            ORDER BY food.sort_order, food.id;",
+        )
+        .bind(restaurant_id)
+        .map(map_food_stock)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(stock) => Ok(stock),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    // starts tracking the food's stock or adjusts it
+    // running out makes the food unavailable, stock coming back undoes that (but not a manual switch)
+    pub async fn set_food_stock(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        food_id: i32,
+        stock: NewFoodStock,
+    ) -> Result<FoodStock, Error> {
+        stock.validate()?;
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        let remaining = stock.remaining.unwrap_or(stock.daily_quantity);
+
+        // `food_stock` before `food`, the order `take_stock` locks them in
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let was_sold_out = sqlx::query_scalar::<_, bool>(
+            "SELECT sold_out FROM food_stock WHERE food_id = $1 FOR UPDATE;",
+        )
+        .bind(food_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .unwrap_or(false);
+        let available = sqlx::query_scalar::<_, bool>(
+            "SELECT available FROM food WHERE id = $1 AND restaurant_id = $2 FOR UPDATE;",
+        )
+        .bind(food_id)
+        .bind(restaurant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        let sold_out = remaining == 0 && (available || was_sold_out);
+        let now_available = if remaining == 0 { false } else { available || was_sold_out };
+
+        sqlx::query(
+            "INSERT INTO food_stock (food_id, daily_quantity, remaining, reset_time, last_reset, sold_out)
+            VALUES ($1, $2, $3, $4, LOCALTIMESTAMP, $5)
+            ON CONFLICT (food_id) DO UPDATE
+            SET daily_quantity = $2, remaining = $3, reset_time = $4, last_reset = LOCALTIMESTAMP, sold_out = $5;",
+        )
+        .bind(food_id)
+        .bind(stock.daily_quantity)
+        .bind(remaining)
+        .bind(stock.reset_time)
+        .bind(sold_out)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        if now_available != available {
+            sqlx::query("UPDATE food SET available = $1, version = version + 1 WHERE id = $2;")
+                .bind(now_available)
+                .bind(food_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+        }
+
+        let stock = sqlx::query(
+            "SELECT food_stock.*, food.name, food.available
+            FROM food_stock
+            JOIN food ON food.id = food_stock.food_id
+            WHERE food_stock.food_id = $1;",
+        )
+        .bind(food_id)
+        .map(map_food_stock)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(stock)
+    }
+
+    // stops tracking, a food that had sold out is available again
+    pub async fn delete_food_stock(
+        &self,
+        account_id: Uuid,
+        restaurant_id: i32,
+        food_id: i32,
+    ) -> Result<bool, Error> {
+        if !self
+            .verify_restaurant_modification_access(restaurant_id, &account_id)
+            .await?
+        {
+            return Err(Error::not_authorized);
+        }
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+        let sold_out = sqlx::query_scalar::<_, bool>(
+            "DELETE FROM food_stock
+            USING food
+            WHERE food_stock.food_id = $1 AND food.id = food_stock.food_id AND food.restaurant_id = $2
+            RETURNING food_stock.sold_out;",
+        )
+        .bind(food_id)
+        .bind(restaurant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        if sold_out == Some(true) {
+            sqlx::query("UPDATE food SET available = TRUE, version = version + 1 WHERE id = $1;")
+                .bind(food_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+        }
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.invalidate_menu(restaurant_id);
+        Ok(sold_out.is_some())
+    }
+
+    // puts back the daily quantity of every stock whose reset time passed since its last reset
+    // called once a minute from main, returns how many foods became available again
+    pub async fn reset_daily_stock(&self) -> Result<usize, Error> {
+        let restaurants = sqlx::query_scalar::<_, i32>(
+            "WITH due AS (
+              SELECT food_id, sold_out FROM food_stock
+              WHERE last_reset < (CASE WHEN LOCALTIME >= reset_time THEN CURRENT_DATE ELSE CURRENT_DATE - 1 END) + reset_time
+              FOR UPDATE
+            ), reset AS (
+              UPDATE food_stock
+              SET remaining = food_stock.daily_quantity,
+                  last_reset = LOCALTIMESTAMP,
+                  sold_out = due.sold_out AND food_stock.daily_quantity = 0
+              FROM due
+              WHERE food_stock.food_id = due.food_id
+              RETURNING food_stock.food_id, due.sold_out AND food_stock.daily_quantity > 0 AS restocked
+            )
+            UPDATE food SET available = TRUE, version = version + 1
+            FROM reset
+            WHERE food.id = reset.food_id AND reset.restocked
+            RETURNING food.restaurant_id;",
+        )
+        .fetch_all(&self.connection)
+        .await
+        .map_err(Error::database_query_error)?;
+
+        for restaurant_id in restaurants.iter().collect::<HashSet<_>>() {
+            self.cache.invalidate_menu(*restaurant_id);
+        }
+        Ok(restaurants.len())
+    }
+}
@@ -0,0 +1,284 @@
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    types::{
+        account::Role,
+        tag::{slugify, NewTag, Tag, TagCount, TagMigration},
+    },
+};
+
+use super::Store;
+
+fn map_tag(row: &PgRow) -> Tag {
+    Tag {
+        id: row.get("id"),
+        slug: row.get("slug"),
+        label_fa: row.get("label_fa"),
+        label_en: row.get("label_en"),
+    }
+}
+
+// replaces the tags of a restaurant and mirrors their slugs into the legacy `tags` column
+pub(super) async fn set_restaurant_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    restaurant_id: i32,
+    tag_ids: &[i32],
+) -> Result<(), Error> {
+    sqlx::query("DELETE FROM restaurant_tag WHERE restaurant_id = $1;")
+        .bind(restaurant_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Error::database_query_error)?;
+
+    sqlx::query(
+        "INSERT INTO restaurant_tag (restaurant_id, tag_id)
+        SELECT $1, UNNEST($2::INT[])
+        ON CONFLICT DO NOTHING;",
+    )
+    .bind(restaurant_id)
+    .bind(tag_ids)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    sqlx::query(
+        "UPDATE restaurant
+        SET tags = ARRAY(SELECT slug FROM tag WHERE id = ANY($2) ORDER BY slug)
+        WHERE id = $1;",
+    )
+    .bind(restaurant_id)
+    .bind(tag_ids)
+    .execute(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+
+    Ok(())
+}
+
+impl Store {
+    // every tag with the number of restaurants using it, optionally limited to one city
+    pub async fn get_tags(&self, city: Option<String>) -> Result<Vec<TagCount>, Error> {
+        match sqlx::query(
+            "SELECT tag.*, COUNT(restaurant.id) AS restaurant_count
+            FROM tag
+            LEFT JOIN restaurant_tag ON restaurant_tag.tag_id = tag.id
+            LEFT JOIN restaurant ON restaurant.id = restaurant_tag.restaurant_id
+              AND ($1::TEXT IS NULL OR restaurant.city = $1)
+            GROUP BY tag.id
+            ORDER BY restaurant_count DESC, tag.slug;",
+        )
+        .bind(city)
+        .map(|row: PgRow| TagCount {
+            tag: map_tag(&row),
+            restaurant_count: row.get("restaurant_count"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(e) => Err(Error::database_query_error(e)),
+        }
+    }
+
+    pub async fn add_tag(&self, account_id: Uuid, tag: NewTag) -> Result<Tag, Error> {
+        match self.verify_role(&account_id, Role::admin).await {
+            Ok(true) => {
+                tag.validate()?;
+                let slug = slugify(&tag.slug);
+                match sqlx::query(
+                    "INSERT INTO tag (slug, label_fa, label_en)
+                    VALUES ($1, $2, $3)
+                    RETURNING *;",
+                )
+                .bind(&slug)
+                .bind(tag.label_fa)
+                .bind(tag.label_en)
+                .map(|row: PgRow| map_tag(&row))
+                .fetch_one(&self.connection)
+                .await
+                {
+                    Ok(tag) => Ok(tag),
+                    Err(e) if Error::is_duplicate_key(&e) => Err(Error::tag_exists(slug)),
+                    Err(e) => Err(Error::database_query_error(e)),
+                }
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    // the legacy `tags` column of linked restaurants follows a slug change
+    pub async fn update_tag(&self, account_id: Uuid, tag_id: i32, tag: NewTag) -> Result<Tag, Error> {
+        match self.verify_role(&account_id, Role::admin).await {
+            Ok(true) => {
+                tag.validate()?;
+                let slug = slugify(&tag.slug);
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+                let tag = sqlx::query(
+                    "UPDATE tag
+                    SET slug = $1, label_fa = $2, label_en = $3
+                    WHERE id = $4
+                    RETURNING *;",
+                )
+                .bind(&slug)
+                .bind(tag.label_fa)
+                .bind(tag.label_en)
+                .bind(tag_id)
+                .map(|row: PgRow| map_tag(&row))
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| {
+                    if Error::is_duplicate_key(&e) {
+                        Error::tag_exists(slug)
+                    } else {
+                        Error::database_query_error(e)
+                    }
+                })?;
+
+                sqlx::query(
+                    "UPDATE restaurant
+                    SET tags = ARRAY(
+                      SELECT tag.slug FROM restaurant_tag
+                      JOIN tag ON tag.id = restaurant_tag.tag_id
+                      WHERE restaurant_tag.restaurant_id = restaurant.id
+                      ORDER BY tag.slug
+                    ),
+                    version = version + 1
+                    WHERE id IN (SELECT restaurant_id FROM restaurant_tag WHERE tag_id = $1);",
+                )
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+
+                sqlx::query("UPDATE food SET tag = $1, version = version + 1 WHERE tag_id = $2;")
+                    .bind(&tag.slug)
+                    .bind(tag_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Error::database_query_error)?;
+
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.clear();
+                Ok(tag)
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn delete_tag(&self, account_id: Uuid, tag_id: i32) -> Result<bool, Error> {
+        match self.verify_role(&account_id, Role::admin).await {
+            Ok(true) => {
+                let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+                // the links go away with the tag, so `tag_ids` of these rows change
+                // and the mirrored slugs are rebuilt without it
+                sqlx::query(
+                    "UPDATE restaurant
+                    SET tags = ARRAY(
+                      SELECT tag.slug FROM restaurant_tag
+                      JOIN tag ON tag.id = restaurant_tag.tag_id
+                      WHERE restaurant_tag.restaurant_id = restaurant.id AND tag.id <> $1
+                      ORDER BY tag.slug
+                    ),
+                    version = version + 1
+                    WHERE id IN (SELECT restaurant_id FROM restaurant_tag WHERE tag_id = $1);",
+                )
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::database_query_error)?;
+
+                sqlx::query("UPDATE food SET version = version + 1 WHERE tag_id = $1;")
+                    .bind(tag_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Error::database_query_error)?;
+
+                sqlx::query("DELETE FROM tag WHERE id = $1;")
+                    .bind(tag_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Error::database_query_error)?;
+
+                tx.commit().await.map_err(Error::database_query_error)?;
+                self.cache.clear();
+                Ok(true)
+            }
+            Ok(false) => Err(Error::not_authorized),
+            Err(e) => Err(e),
+        }
+    }
+
+    // turns the free text restaurant `tags` and food `tag` into managed tags and links them
+    // safe to run more than once, existing slugs are reused
+    // the text is used for both labels, admins can fix the english/persian label afterwards
+    pub async fn migrate_free_text_tags(&self) -> Result<TagMigration, Error> {
+        let mut tx = self.connection.begin().await.map_err(Error::database_query_error)?;
+
+        let tags_created = sqlx::query(
+            "INSERT INTO tag (slug, label_fa, label_en)
+            SELECT DISTINCT ON (slug) slug, label, label FROM (
+              SELECT LOWER(REGEXP_REPLACE(TRIM(label), '\\s+', '-', 'g')) AS slug, TRIM(label) AS label
+              FROM (
+                SELECT UNNEST(tags) AS label FROM restaurant
+                UNION
+                SELECT tag AS label FROM food
+              ) free_text
+              WHERE TRIM(label) <> ''
+            ) slugs
+            ON CONFLICT (slug) DO NOTHING;",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .rows_affected();
+
+        let restaurants_linked = sqlx::query(
+            "INSERT INTO restaurant_tag (restaurant_id, tag_id)
+            SELECT DISTINCT restaurant.id, tag.id
+            FROM restaurant
+            CROSS JOIN LATERAL UNNEST(restaurant.tags) AS free_text(label)
+            JOIN tag ON tag.slug = LOWER(REGEXP_REPLACE(TRIM(free_text.label), '\\s+', '-', 'g'))
+            ON CONFLICT DO NOTHING;",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .rows_affected();
+
+        // new links change `tag_ids`, a migration is rare enough to bump every linked restaurant
+        if restaurants_linked > 0 {
+            sqlx::query(
+                "UPDATE restaurant SET version = version + 1
+                WHERE id IN (SELECT restaurant_id FROM restaurant_tag);",
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::database_query_error)?;
+        }
+
+        let foods_linked = sqlx::query(
+            "UPDATE food SET tag_id = tag.id, version = food.version + 1
+            FROM tag
+            WHERE food.tag_id IS NULL
+            AND tag.slug = LOWER(REGEXP_REPLACE(TRIM(food.tag), '\\s+', '-', 'g'));",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::database_query_error)?
+        .rows_affected();
+
+        tx.commit().await.map_err(Error::database_query_error)?;
+        self.cache.clear();
+        Ok(TagMigration {
+            tags_created,
+            restaurants_linked,
+            foods_linked,
+        })
+    }
+}
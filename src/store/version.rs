@@ -0,0 +1,24 @@
+use sqlx::{Postgres, Transaction};
+
+use crate::{
+    error::Error,
+    utils::etag::{version_etag, IfMatch},
+};
+
+// locks a versioned row (`restaurant`, `food`) until the transaction ends and checks it against `If-Match`
+// a missing row is reported like any other `fetch_one`
+pub(super) async fn check_version(
+    tx: &mut Transaction<'_, Postgres>,
+    table: &'static str,
+    id: i32,
+    if_match: &IfMatch,
+) -> Result<(), Error> {
+    let version = sqlx::query_scalar::<_, i32>(&format!(
+        "SELECT version FROM {table} WHERE id = $1 FOR UPDATE;"
+    ))
+    .bind(id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(Error::database_query_error)?;
+    if_match.check(&version_etag(version))
+}
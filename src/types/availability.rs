@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+
+use super::{
+    food::Food,
+    restaurant::{is_open_at, HoursInterval, Weekday},
+};
+
+// the weekly windows a food or a whole menu section can be ordered in ("breakfast until 11:00")
+// same model as the opening hours: several intervals per day, an interval may pass midnight
+// no windows means any time, a food in a section has to be inside the windows of both
+#[derive(Debug, Default, Clone)]
+pub struct Schedules {
+    pub foods: HashMap<i32, Vec<HoursInterval>>,
+    pub sections: HashMap<i32, Vec<HoursInterval>>,
+}
+
+// a section's `available_from`/`available_to` as a window on every day of the week
+pub fn daily_window(from: NaiveTime, to: NaiveTime) -> Vec<HoursInterval> {
+    let mut day = Weekday::Saturday;
+    let mut week = Vec::with_capacity(7);
+    for _ in 0..7 {
+        week.push(HoursInterval {
+            day_of_week: day.clone(),
+            open_time: from,
+            close_time: to,
+        });
+        day = day.next();
+    }
+    week
+}
+
+impl Schedules {
+    pub fn is_orderable_at(&self, food: &Food, now: NaiveDateTime) -> bool {
+        let day = Weekday::from(now.weekday());
+        let time = now.time();
+        let within = |windows: Option<&Vec<HoursInterval>>| {
+            windows.is_none_or(|windows| windows.is_empty() || is_open_at(windows, &day, time))
+        };
+        food.available
+            && within(self.foods.get(&food.id))
+            && food
+                .section_id
+                .is_none_or(|section_id| within(self.sections.get(&section_id)))
+    }
+}
+
+// what `GET`/`PUT .../schedule` is about
+#[derive(Debug, Clone, Copy)]
+pub enum ScheduleTarget {
+    Food(i32),
+    Section(i32),
+}
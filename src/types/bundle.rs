@@ -0,0 +1,230 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+use super::{dietary::DietaryFilter, food::Food};
+
+// a combo sold at its own price ("Burger menu": burger + fries + any drink)
+// `items` are always part of it, every slot is filled with one of its `choices` by the customer
+// promotions don't apply to a bundle, its price is the deal
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bundle {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub section_id: Option<i32>,
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    pub price: i32,
+    pub available: bool,
+    pub sort_order: i32,
+    pub items: Vec<BundleItem>,
+    pub slots: Vec<BundleSlot>,
+    // `available` and every item too, and every slot has an available choice
+    pub orderable: bool,
+}
+
+// `available` is the food's, a bundle with an unavailable item can't be ordered
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleItem {
+    pub food_id: i32,
+    pub name: String,
+    pub quantity: i32,
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleSlot {
+    pub id: i32,
+    pub name: String,
+    pub quantity: i32,
+    pub choices: Vec<BundleChoice>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleChoice {
+    pub food_id: i32,
+    pub name: String,
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewBundle {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub image: String,
+    pub price: i32,
+    pub section_id: Option<i32>,
+    #[serde(default = "default_available")]
+    pub available: bool,
+    #[serde(default)]
+    pub items: Vec<NewBundleItem>,
+    #[serde(default)]
+    pub slots: Vec<NewBundleSlot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewBundleItem {
+    pub food_id: i32,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+// `choices` are food ids
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewBundleSlot {
+    pub name: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    pub choices: Vec<i32>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+impl NewBundle {
+    pub fn validate(&self) -> Result<(), Error> {
+        let item_ids: HashSet<i32> = self.items.iter().map(|item| item.food_id).collect();
+        if self.name.trim().is_empty() {
+            Err(Error::invalid_field("name is empty".to_string()))
+        } else if self.price < 0 {
+            Err(Error::invalid_field("price can't be negative".to_string()))
+        } else if self.items.is_empty() && self.slots.is_empty() {
+            Err(Error::invalid_field("a bundle needs items or slots".to_string()))
+        } else if item_ids.len() != self.items.len() {
+            Err(Error::invalid_field("a food is in items twice, raise its quantity instead".to_string()))
+        } else if self.items.iter().any(|item| item.quantity < 1)
+            || self.slots.iter().any(|slot| slot.quantity < 1)
+        {
+            Err(Error::invalid_field("quantities must be positive".to_string()))
+        } else if let Some(slot) = self.slots.iter().find(|slot| slot.name.trim().is_empty()) {
+            Err(Error::invalid_field(format!("a slot with {} choices has no name", slot.choices.len())))
+        } else if let Some(slot) = self.slots.iter().find(|slot| {
+            slot.choices.is_empty()
+                || slot.choices.iter().collect::<HashSet<_>>().len() != slot.choices.len()
+        }) {
+            Err(Error::invalid_field(format!(
+                "`{}` needs at least one choice and no food twice",
+                slot.name
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    // every food the bundle refers to
+    pub fn food_ids(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self
+            .items
+            .iter()
+            .map(|item| item.food_id)
+            .chain(self.slots.iter().flat_map(|slot| slot.choices.iter().copied()))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+// a bundle's choice for one slot in `PUT /order`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct SlotChoice {
+    pub slot_id: i32,
+    pub food_id: i32,
+}
+
+// a food of a bundle in the cart, as it was when the bundle was added
+// `quantity` is per bundle, `slot_name` is set for the foods the customer chose
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ItemComponent {
+    pub food_id: Option<i32>,
+    pub slot_name: Option<String>,
+    pub name: String,
+    pub quantity: i32,
+}
+
+impl Bundle {
+    pub fn is_orderable(&self) -> bool {
+        self.available
+            && self.items.iter().all(|item| item.available)
+            && self
+                .slots
+                .iter()
+                .all(|slot| slot.choices.iter().any(|choice| choice.available))
+    }
+
+    // checks the customer's choices, one available food for every slot
+    // returns the bundle's foods in bundle order, items first
+    pub fn components(&self, choices: &[SlotChoice]) -> Result<Vec<ItemComponent>, Error> {
+        if !self.available {
+            return Err(Error::invalid_options(format!("`{}` is not available", self.name)));
+        }
+        if let Some(item) = self.items.iter().find(|item| !item.available) {
+            return Err(Error::invalid_options(format!("`{}` is not available", item.name)));
+        }
+        if choices.len() != self.slots.len() {
+            return Err(Error::invalid_options("pick one food for every slot".to_string()));
+        }
+
+        let mut components: Vec<ItemComponent> = self
+            .items
+            .iter()
+            .map(|item| ItemComponent {
+                food_id: Some(item.food_id),
+                slot_name: None,
+                name: item.name.clone(),
+                quantity: item.quantity,
+            })
+            .collect();
+        for slot in &self.slots {
+            let choice = choices
+                .iter()
+                .find(|choice| choice.slot_id == slot.id)
+                .and_then(|choice| slot.choices.iter().find(|food| food.food_id == choice.food_id))
+                .ok_or_else(|| Error::invalid_options(format!("pick one food for `{}`", slot.name)))?;
+            if !choice.available {
+                return Err(Error::invalid_options(format!("`{}` is not available", choice.name)));
+            }
+            components.push(ItemComponent {
+                food_id: Some(choice.food_id),
+                slot_name: Some(slot.name.clone()),
+                name: choice.name.clone(),
+                quantity: slot.quantity,
+            });
+        }
+        Ok(components)
+    }
+
+    // a filtered menu keeps a bundle when all its items pass and every slot keeps a choice that does
+    pub fn filter(mut self, filter: &DietaryFilter, foods: &HashMap<i32, &Food>) -> Option<Bundle> {
+        let passes = |food_id: i32| foods.get(&food_id).is_some_and(|food| filter.matches(food));
+        if !self.items.iter().all(|item| passes(item.food_id)) {
+            return None;
+        }
+        for slot in &mut self.slots {
+            slot.choices.retain(|choice| passes(choice.food_id));
+        }
+        self.orderable = self.is_orderable();
+        self.slots.iter().all(|slot| !slot.choices.is_empty()).then_some(self)
+    }
+}
+
+// identifies the choices of a bundle in the cart, "" when it has no slots
+pub fn choices_key(choices: &[SlotChoice]) -> String {
+    let mut pairs: Vec<(i32, i32)> = choices.iter().map(|choice| (choice.slot_id, choice.food_id)).collect();
+    pairs.sort_unstable();
+    pairs
+        .iter()
+        .map(|(slot_id, food_id)| format!("{slot_id}:{food_id}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
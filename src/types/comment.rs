@@ -20,6 +20,8 @@ pub struct NewComment {
     pub created_on: NaiveDateTime,
 }
 
+// votes are counted in SQL (`likes`), this one mirrors `comment_votes`
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CommentVote {
     pub account_id: Uuid,
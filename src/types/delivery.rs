@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// a zone is either a radius band around the restaurant or a polygon drawn by the owner
+// zones may overlap (e.g. 0-2km and 0-5km bands), see `pick_zone` for which one applies
+// points are `[lat, lon]`, same as `Restaurant.location`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ZoneArea {
+    Radius { radius_km: f64 },
+    Polygon { points: Vec<[f64; 2]> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryZone {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub name: String,
+    pub area: ZoneArea,
+    pub fee: i32,
+    pub minimum_order: i32,
+    pub estimated_minutes: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewDeliveryZone {
+    pub name: String,
+    pub area: ZoneArea,
+    pub fee: i32,
+    pub minimum_order: i32,
+    pub estimated_minutes: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryCheck {
+    pub location: [f64; 2],
+    // cart total, if given we also tell whether the minimum order is reached
+    pub subtotal: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Deliverability {
+    pub deliverable: bool,
+    pub distance_km: f64,
+    pub zone_id: Option<i32>,
+    pub fee: Option<i32>,
+    pub minimum_order: Option<i32>,
+    pub estimated_minutes: Option<i32>,
+    pub meets_minimum: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryAddress {
+    pub location: [f64; 2],
+}
+
+impl ZoneArea {
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            ZoneArea::Radius { radius_km } if *radius_km > 0.0 => Ok(()),
+            ZoneArea::Polygon { points } if points.len() >= 3 => Ok(()),
+            _ => Err(Error::invalid_delivery_zone),
+        }
+    }
+
+    pub fn contains(&self, restaurant: [f64; 2], point: [f64; 2]) -> bool {
+        match self {
+            ZoneArea::Radius { radius_km } => distance_km(restaurant, point) <= *radius_km,
+            ZoneArea::Polygon { points } => point_in_polygon(points, point),
+        }
+    }
+
+    // the database keeps both shapes in nullable columns, polygons as a flat [lat, lon, lat, lon, ...] array
+    pub fn from_columns(radius_km: Option<f64>, polygon: Option<Vec<f64>>) -> Self {
+        match (radius_km, polygon) {
+            (Some(radius_km), _) => ZoneArea::Radius { radius_km },
+            (None, polygon) => ZoneArea::Polygon {
+                points: polygon
+                    .unwrap_or_default()
+                    .chunks_exact(2)
+                    .map(|p| [p[0], p[1]])
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn to_columns(&self) -> (Option<f64>, Option<Vec<f64>>) {
+        match self {
+            ZoneArea::Radius { radius_km } => (Some(*radius_km), None),
+            ZoneArea::Polygon { points } => (None, Some(points.iter().flatten().copied().collect())),
+        }
+    }
+}
+
+// haversine, good enough for delivery distances
+pub fn distance_km(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (lat1, lon1) = (a[0].to_radians(), a[1].to_radians());
+    let (lat2, lon2) = (b[0].to_radians(), b[1].to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+// ray casting on plain lat/lon, zones are small enough that the curvature doesn't matter
+fn point_in_polygon(polygon: &[[f64; 2]], point: [f64; 2]) -> bool {
+    let (y, x) = (point[0], point[1]);
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        let (yi, xi) = (polygon[i][0], polygon[i][1]);
+        let (yj, xj) = (polygon[j][0], polygon[j][1]);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+// cheapest matching zone wins, then the fastest one
+pub fn pick_zone(
+    zones: &[DeliveryZone],
+    restaurant: [f64; 2],
+    point: [f64; 2],
+) -> Option<&DeliveryZone> {
+    zones
+        .iter()
+        .filter(|zone| zone.area.contains(restaurant, point))
+        .min_by_key(|zone| (zone.fee, zone.estimated_minutes))
+}
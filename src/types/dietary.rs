@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use serde::{
+    de::{value::StrDeserializer, DeserializeOwned, IntoDeserializer},
+    Deserialize, Serialize,
+};
+use sqlx::prelude::Type;
+
+use crate::error::Error;
+
+use super::food::Food;
+
+pub const MAX_SPICY_LEVEL: i32 = 3;
+
+// the 14 allergens food labels have to declare
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Type)]
+#[sqlx(type_name = "text")]
+#[sqlx(rename_all = "lowercase")]
+#[allow(non_camel_case_types)]
+pub enum Allergen {
+    gluten,
+    crustaceans,
+    eggs,
+    fish,
+    peanuts,
+    soy,
+    dairy,
+    nuts,
+    celery,
+    mustard,
+    sesame,
+    sulphites,
+    lupin,
+    molluscs,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Type)]
+#[sqlx(type_name = "text")]
+#[sqlx(rename_all = "lowercase")]
+#[allow(non_camel_case_types)]
+pub enum Diet {
+    vegetarian,
+    vegan,
+    halal,
+}
+
+// a vegan food is vegetarian too, stored that way so filters only have to look for the label
+pub fn normalize_diets(diets: &mut Vec<Diet>) {
+    if diets.contains(&Diet::vegan) {
+        diets.push(Diet::vegetarian);
+    }
+    let mut seen = Vec::with_capacity(diets.len());
+    diets.retain(|diet| {
+        let new = !seen.contains(diet);
+        seen.push(*diet);
+        new
+    });
+}
+
+// labels that contradict the allergens, e.g. a vegan food with dairy
+pub fn validate_labels(allergens: &[Allergen], diets: &[Diet], spicy_level: i32) -> Result<(), Error> {
+    let animal = [Allergen::fish, Allergen::crustaceans, Allergen::molluscs];
+    if !(0..=MAX_SPICY_LEVEL).contains(&spicy_level) {
+        Err(Error::invalid_field(format!(
+            "spicy_level is between 0 and {MAX_SPICY_LEVEL}"
+        )))
+    } else if diets.contains(&Diet::vegan)
+        && allergens
+            .iter()
+            .any(|a| animal.contains(a) || matches!(a, Allergen::dairy | Allergen::eggs))
+    {
+        Err(Error::invalid_field("a vegan food can't contain animal products".to_string()))
+    } else if diets.contains(&Diet::vegetarian) && allergens.iter().any(|a| animal.contains(a)) {
+        Err(Error::invalid_field("a vegetarian food can't contain fish or seafood".to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+// `?exclude_allergen=nuts,dairy&diet=vegan&max_spicy=1`, every part is optional
+#[derive(Debug, Default, Clone)]
+pub struct DietaryFilter {
+    pub exclude_allergens: Vec<Allergen>,
+    pub diets: Vec<Diet>,
+    pub max_spicy: Option<i32>,
+}
+
+// comma separated labels, "nuts,dairy"
+fn parse_labels<T: DeserializeOwned>(value: &str) -> Result<Vec<T>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(|label| {
+            let deserializer: StrDeserializer<serde::de::value::Error> = label.into_deserializer();
+            T::deserialize(deserializer)
+                .map_err(|_| Error::invalid_field(format!("unknown label `{label}`")))
+        })
+        .collect()
+}
+
+impl DietaryFilter {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let mut filter = DietaryFilter::default();
+        if let Some(allergens) = params.get("exclude_allergen") {
+            filter.exclude_allergens = parse_labels(allergens)?;
+        }
+        if let Some(diets) = params.get("diet") {
+            filter.diets = parse_labels(diets)?;
+        }
+        if let Some(max_spicy) = params.get("max_spicy") {
+            filter.max_spicy = Some(max_spicy.parse().map_err(|_| Error::parse_error)?);
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exclude_allergens.is_empty() && self.diets.is_empty() && self.max_spicy.is_none()
+    }
+
+    // a food without declared allergens can't be shown as free of any of them
+    pub fn matches(&self, food: &Food) -> bool {
+        let allergen_free = match &food.allergens {
+            Some(allergens) => !allergens.iter().any(|a| self.exclude_allergens.contains(a)),
+            None => self.exclude_allergens.is_empty(),
+        };
+        allergen_free
+            && self.diets.iter().all(|diet| food.diets.contains(diet))
+            && self.max_spicy.is_none_or(|max| food.spicy_level <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::types::food::test_food;
+
+    fn food(allergens: Option<&[Allergen]>) -> Food {
+        Food {
+            allergens: allergens.map(<[Allergen]>::to_vec),
+            ..test_food(1, 1000)
+        }
+    }
+
+    fn filter(exclude_allergen: &str) -> DietaryFilter {
+        let params = HashMap::from([("exclude_allergen".to_string(), exclude_allergen.to_string())]);
+        DietaryFilter::from_params(&params).unwrap()
+    }
+
+    #[test]
+    fn undeclared_allergens_fail_an_allergen_filter() {
+        assert!(!filter("nuts").matches(&food(None)));
+        assert!(DietaryFilter::default().matches(&food(None)));
+    }
+
+    #[test]
+    fn declared_allergens_are_checked() {
+        assert!(filter("nuts").matches(&food(Some(&[]))));
+        assert!(filter("nuts").matches(&food(Some(&[Allergen::gluten]))));
+        assert!(!filter("nuts,dairy").matches(&food(Some(&[Allergen::dairy]))));
+    }
+
+    #[test]
+    fn missing_allergens_are_undeclared() {
+        // `test_food` leaves `allergens` out of its JSON
+        assert_eq!(test_food(1, 1000).allergens, None);
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+use super::{food::Food, restaurant::Restaurant};
+
+// `GET /me/favorites?kind=restaurants` only fills `restaurants` and vice versa
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Favorites {
+    pub restaurants: Vec<Restaurant>,
+    pub foods: Vec<Food>,
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use sqlx::Type;
 
+use crate::error::Error;
+
+use super::dietary::{validate_labels, Allergen, Diet};
+
 #[derive(Serialize, Deserialize, Clone, Debug, Type)]
 pub struct Food {
     pub id: i32,
@@ -8,11 +12,74 @@ pub struct Food {
     pub name: String,
     pub image: String,
     pub tag: String,
+    // managed tag, when set `tag` holds its slug
+    pub tag_id: Option<i32>,
+    // discounts are promotions now, see `types::promotion`
     pub price: i32,
-    pub discount: bool,
-    pub discount_price: Option<i32>,
     pub ingredient: Vec<String>,
     pub available: bool,
+    // structured labels next to the free text `ingredient`, see `types::dietary`
+    // `None` when the restaurant hasn't declared the allergens, `[]` when there are none
+    #[serde(default)]
+    pub allergens: Option<Vec<Allergen>>,
+    #[serde(default)]
+    pub diets: Vec<Diet>,
+    // 0 (not spicy) to 3
+    #[serde(default)]
+    pub spicy_level: i32,
+    // read only, average of the dish reviews, 0 without reviews
+    #[serde(default)]
+    pub rating: f64,
+    #[serde(default)]
+    pub rating_count: i32,
+    // menu section, has to belong to the same restaurant
+    #[serde(default)]
+    pub section_id: Option<i32>,
+    // position inside the section, read only, see `PUT /restaurants/{id}/sections/{section_id}/order`
+    #[serde(default)]
+    pub sort_order: i32,
+    // read only, writes are checked with `If-Match` instead
+    #[serde(default)]
+    pub version: i32,
+    // read only, the price to pay now: `price` with the best running promotion
+    #[serde(default)]
+    pub effective_price: i32,
+    #[serde(default)]
+    pub promotion_id: Option<i32>,
+    // read only, `available` and inside the food's and its section's windows, see `types::availability`
+    #[serde(default)]
+    pub orderable_now: bool,
+}
+
+impl Food {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            Err(Error::invalid_field("name is empty".to_string()))
+        } else if self.tag.trim().is_empty() {
+            Err(Error::invalid_field("tag is empty".to_string()))
+        } else if self.price < 0 {
+            Err(Error::invalid_field("price can't be negative".to_string()))
+        } else {
+            validate_labels(self.allergens.as_deref().unwrap_or_default(), &self.diets, self.spicy_level)
+        }
+    }
+}
+
+// the food the unit tests start from, read from JSON so the `#[serde(default)]` fields get their defaults
+#[cfg(test)]
+pub fn test_food(id: i32, price: i32) -> Food {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "restaurant_id": 1,
+        "name": "Kebab",
+        "image": "",
+        "tag": "kebab",
+        "tag_id": null,
+        "price": price,
+        "ingredient": [],
+        "available": true,
+    }))
+    .unwrap()
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Type)]
@@ -21,9 +88,16 @@ pub struct NewFood {
     pub name: String,
     pub image: String,
     pub tag: String,
+    pub tag_id: Option<i32>,
     pub price: i32,
-    pub discount: bool,
-    pub discount_price: Option<i32>,
     pub ingredient: Vec<String>,
     pub available: bool,
+    #[serde(default)]
+    pub allergens: Option<Vec<Allergen>>,
+    #[serde(default)]
+    pub diets: Vec<Diet>,
+    #[serde(default)]
+    pub spicy_level: i32,
+    #[serde(default)]
+    pub section_id: Option<i32>,
 }
@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+use super::food::Food;
+
+// sizes and add-ons of a food ("Size": small/large, "Extras": cheese, olives)
+// a customer picks between `min_select` and `max_select` options of every group
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OptionGroup {
+    pub id: i32,
+    pub food_id: i32,
+    pub name: String,
+    pub min_select: i32,
+    pub max_select: i32,
+    pub sort_order: i32,
+    pub options: Vec<FoodOption>,
+}
+
+// `price_delta` is added to the food's price (or discount price), it can be negative (e.g. small size)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FoodOption {
+    pub id: i32,
+    pub group_id: i32,
+    pub name: String,
+    pub price_delta: i32,
+    pub available: bool,
+    pub sort_order: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewOptionGroup {
+    pub name: String,
+    #[serde(default)]
+    pub min_select: i32,
+    #[serde(default = "default_max_select")]
+    pub max_select: i32,
+    pub options: Vec<NewFoodOption>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewFoodOption {
+    pub name: String,
+    #[serde(default)]
+    pub price_delta: i32,
+    #[serde(default = "default_available")]
+    pub available: bool,
+}
+
+fn default_max_select() -> i32 {
+    1
+}
+
+fn default_available() -> bool {
+    true
+}
+
+impl NewOptionGroup {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            Err(Error::invalid_field("option group name is empty".to_string()))
+        } else if self.options.iter().any(|option| option.name.trim().is_empty()) {
+            Err(Error::invalid_field(format!("an option of `{}` has no name", self.name)))
+        } else if self.min_select < 0 || self.max_select < 1 || self.min_select > self.max_select {
+            Err(Error::invalid_field(format!(
+                "`{}` needs 0 <= min_select <= max_select and max_select >= 1",
+                self.name
+            )))
+        } else if self.min_select as usize > self.options.len() {
+            Err(Error::invalid_field(format!(
+                "`{}` has fewer options than min_select",
+                self.name
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    // the most a customer can take off the food's price with this group
+    // the `min_select` cheapest options, then every other negative one up to `max_select`
+    // summed in i64, the deltas are whatever was sent and can add up past i32
+    pub fn cheapest_delta(&self) -> i64 {
+        let mut deltas: Vec<i32> = self.options.iter().map(|option| option.price_delta).collect();
+        deltas.sort_unstable();
+        deltas
+            .iter()
+            .enumerate()
+            .take(self.max_select.max(0) as usize)
+            .filter(|(i, delta)| (*i as i32) < self.min_select || **delta < 0)
+            .map(|(_, delta)| *delta as i64)
+            .sum()
+    }
+}
+
+// an option as it was chosen, kept on the cart item so later menu changes don't alter it without the customer knowing
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ItemOption {
+    pub option_id: Option<i32>,
+    pub group_name: String,
+    pub name: String,
+    pub price_delta: i32,
+}
+
+// checks the chosen option ids against the food's groups
+// the options come back in menu order, whatever order they were sent in
+pub fn select_options(groups: &[OptionGroup], chosen: &[i32]) -> Result<Vec<ItemOption>, Error> {
+    let chosen_set: HashSet<i32> = chosen.iter().copied().collect();
+    if chosen_set.len() != chosen.len() {
+        return Err(Error::invalid_options("an option is chosen twice".to_string()));
+    }
+
+    let mut selected = Vec::new();
+    for group in groups {
+        let picked: Vec<&FoodOption> = group
+            .options
+            .iter()
+            .filter(|option| chosen_set.contains(&option.id))
+            .collect();
+        if let Some(option) = picked.iter().find(|option| !option.available) {
+            return Err(Error::invalid_options(format!("`{}` is not available", option.name)));
+        }
+        let count = picked.len() as i32;
+        if count < group.min_select || count > group.max_select {
+            return Err(Error::invalid_options(format!(
+                "pick between {} and {} of `{}`",
+                group.min_select, group.max_select, group.name
+            )));
+        }
+        selected.extend(picked.into_iter().map(|option| ItemOption {
+            option_id: Some(option.id),
+            group_name: group.name.clone(),
+            name: option.name.clone(),
+            price_delta: option.price_delta,
+        }));
+    }
+
+    if selected.len() != chosen.len() {
+        return Err(Error::invalid_options(
+            "an option doesn't belong to this food".to_string(),
+        ));
+    }
+    Ok(selected)
+}
+
+// identifies an option combination of a cart item, "" when there are no options
+pub fn options_key(options: &[ItemOption]) -> String {
+    let ids: Vec<i32> = options.iter().filter_map(|option| option.option_id).collect();
+    chosen_key(&ids)
+}
+
+// the same key from the option ids a client sent, to find the item again without checking them
+pub fn chosen_key(chosen: &[i32]) -> String {
+    let mut ids = chosen.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// a food of `GET /restaurants/{id}/food` with the groups to choose from
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MenuFood {
+    #[serde(flatten)]
+    pub food: Food,
+    pub option_groups: Vec<OptionGroup>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(id: i32, name: &str, price_delta: i32, available: bool) -> FoodOption {
+        FoodOption {
+            id,
+            group_id: 0,
+            name: name.to_string(),
+            price_delta,
+            available,
+            sort_order: id,
+        }
+    }
+
+    fn groups() -> Vec<OptionGroup> {
+        vec![
+            OptionGroup {
+                id: 1,
+                food_id: 1,
+                name: "Size".to_string(),
+                min_select: 1,
+                max_select: 1,
+                sort_order: 0,
+                options: vec![option(1, "Small", -20000, true), option(2, "Large", 30000, true)],
+            },
+            OptionGroup {
+                id: 2,
+                food_id: 1,
+                name: "Extras".to_string(),
+                min_select: 0,
+                max_select: 2,
+                sort_order: 1,
+                options: vec![
+                    option(3, "Cheese", 15000, true),
+                    option(4, "Olives", 10000, true),
+                    option(5, "Mushrooms", 12000, false),
+                ],
+            },
+        ]
+    }
+
+    fn error(result: Result<Vec<ItemOption>, Error>) -> String {
+        match result {
+            Err(Error::invalid_options(message)) => message,
+            other => panic!("expected invalid options, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn options_come_back_in_menu_order() {
+        let options = select_options(&groups(), &[4, 2, 3]).unwrap();
+        let names: Vec<&str> = options.iter().map(|option| option.name.as_str()).collect();
+        assert_eq!(names, ["Large", "Cheese", "Olives"]);
+        assert_eq!(options.iter().map(|option| option.price_delta).sum::<i32>(), 55000);
+        assert_eq!(options[0].group_name, "Size");
+    }
+
+    #[test]
+    fn group_limits_are_enforced() {
+        assert_eq!(error(select_options(&groups(), &[])), "pick between 1 and 1 of `Size`");
+        assert_eq!(error(select_options(&groups(), &[1, 2])), "pick between 1 and 1 of `Size`");
+        assert_eq!(
+            error(select_options(&groups(), &[1, 3, 4, 5])),
+            "`Mushrooms` is not available"
+        );
+    }
+
+    #[test]
+    fn unknown_and_repeated_options_are_rejected() {
+        assert_eq!(
+            error(select_options(&groups(), &[1, 99])),
+            "an option doesn't belong to this food"
+        );
+        assert_eq!(error(select_options(&groups(), &[1, 1])), "an option is chosen twice");
+    }
+
+    #[test]
+    fn key_ignores_order() {
+        let a = select_options(&groups(), &[4, 2, 3]).unwrap();
+        let b = select_options(&groups(), &[3, 4, 2]).unwrap();
+        assert_eq!(options_key(&a), "2,3,4");
+        assert_eq!(options_key(&a), options_key(&b));
+        assert_eq!(chosen_key(&[4, 2, 3]), options_key(&a));
+        assert_eq!(options_key(&[]), "");
+        assert_eq!(chosen_key(&[]), "");
+    }
+
+    fn new_group(min_select: i32, max_select: i32, deltas: &[i32]) -> NewOptionGroup {
+        NewOptionGroup {
+            name: "Group".to_string(),
+            min_select,
+            max_select,
+            options: deltas
+                .iter()
+                .map(|price_delta| NewFoodOption {
+                    name: price_delta.to_string(),
+                    price_delta: *price_delta,
+                    available: true,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn cheapest_delta() {
+        assert_eq!(new_group(1, 1, &[-20000, 30000]).cheapest_delta(), -20000);
+        assert_eq!(new_group(0, 3, &[-5000, -3000, 1000]).cheapest_delta(), -8000);
+        assert_eq!(new_group(0, 1, &[-5000, -3000]).cheapest_delta(), -5000);
+        assert_eq!(new_group(2, 3, &[1000, 2000, 3000]).cheapest_delta(), 3000);
+        assert_eq!(new_group(0, 2, &[1000]).cheapest_delta(), 0);
+        assert_eq!(new_group(0, 2, &[i32::MIN, i32::MIN]).cheapest_delta(), 2 * i32::MIN as i64);
+    }
+}
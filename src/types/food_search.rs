@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+use super::food::Food;
+
+const DEFAULT_LIMIT: i32 = 20;
+const MAX_LIMIT: i32 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FoodSort {
+    #[default]
+    Relevance,
+    Price,
+    Rating,
+}
+
+// `GET /food/search?q=&city=&max_price=&available=true&sort=price&limit=&offset=`
+#[derive(Debug, Clone)]
+pub struct FoodSearch {
+    pub q: String,
+    pub city: Option<String>,
+    // compared with `effective_price`, so running promotions count
+    pub max_price: Option<i32>,
+    pub available_only: bool,
+    pub sort: FoodSort,
+    pub limit: i32,
+    pub offset: i32,
+}
+
+fn parse_param<T: std::str::FromStr>(params: &HashMap<String, String>, key: &str) -> Result<Option<T>, Error> {
+    params
+        .get(key)
+        .map(|value| value.parse().map_err(|_| Error::parse_error))
+        .transpose()
+}
+
+impl FoodSearch {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let q = params.get("q").map(|q| q.trim().to_string()).unwrap_or_default();
+        if q.is_empty() {
+            return Err(Error::missing_parameters);
+        }
+        let sort = match params.get("sort").map(|sort| sort.to_lowercase()).as_deref() {
+            None | Some("relevance") => FoodSort::Relevance,
+            Some("price") => FoodSort::Price,
+            Some("rating") => FoodSort::Rating,
+            Some(_) => return Err(Error::parse_error),
+        };
+        Ok(FoodSearch {
+            q,
+            city: params.get("city").cloned().filter(|city| !city.trim().is_empty()),
+            max_price: parse_param(params, "max_price")?,
+            available_only: parse_param(params, "available")?.unwrap_or(false),
+            sort,
+            limit: parse_param(params, "limit")?
+                .unwrap_or(DEFAULT_LIMIT)
+                .clamp(1, MAX_LIMIT),
+            offset: parse_param(params, "offset")?.unwrap_or(0).max(0),
+        })
+    }
+}
+
+// the part of a restaurant a search result needs
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RestaurantSummary {
+    pub id: i32,
+    pub name: String,
+    pub city: String,
+    pub image: String,
+    pub rating: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FoodSearchResult {
+    #[serde(flatten)]
+    pub food: Food,
+    pub restaurant: RestaurantSummary,
+    // higher is better: a name match beats a tag match, which beats an ingredient match
+    pub relevance: f64,
+}
+
+impl FoodSearchResult {
+    // best first, ties fall back to relevance, then rating, then price
+    pub fn sort(results: &mut [FoodSearchResult], sort: FoodSort) {
+        let by_relevance = |a: &FoodSearchResult, b: &FoodSearchResult| b.relevance.total_cmp(&a.relevance);
+        let by_rating = |a: &FoodSearchResult, b: &FoodSearchResult| {
+            b.restaurant.rating.total_cmp(&a.restaurant.rating)
+        };
+        let by_price = |a: &FoodSearchResult, b: &FoodSearchResult| {
+            a.food.effective_price.cmp(&b.food.effective_price)
+        };
+        results.sort_by(|a, b| match sort {
+            FoodSort::Relevance => by_relevance(a, b)
+                .then_with(|| by_rating(a, b))
+                .then_with(|| by_price(a, b)),
+            FoodSort::Price => by_price(a, b)
+                .then_with(|| by_relevance(a, b))
+                .then_with(|| by_rating(a, b)),
+            FoodSort::Rating => by_rating(a, b)
+                .then_with(|| by_relevance(a, b))
+                .then_with(|| by_price(a, b)),
+        });
+    }
+}
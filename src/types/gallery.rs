@@ -0,0 +1,20 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// at most one image per restaurant is the cover, its url is mirrored in `restaurant.image`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RestaurantImage {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub url: String,
+    pub caption: Option<String>,
+    pub sort_order: i32,
+    pub is_cover: bool,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateRestaurantImage {
+    pub caption: Option<String>,
+    pub is_cover: bool,
+}
@@ -0,0 +1,67 @@
+// # Language:
+// picked from the `Accept-Language` header, e.g. `fa-IR,fa;q=0.9,en;q=0.8`
+// anything we don't support falls back to English
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Persian,
+}
+
+impl Language {
+    pub fn from_accept_language(header: Option<String>) -> Self {
+        let header = match header {
+            Some(header) => header,
+            None => return Language::default(),
+        };
+
+        let mut ranges: Vec<(f32, Language)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.trim().split(';');
+                let tag = parts.next()?.trim().to_lowercase();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                let language = match tag.split('-').next()? {
+                    "fa" | "per" | "fas" => Language::Persian,
+                    "en" => Language::English,
+                    _ => return None,
+                };
+                Some((quality, language))
+            })
+            .collect();
+
+        // stable sort keeps header order for equal weights
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranges
+            .first()
+            .map(|(_, language)| *language)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Language;
+
+    #[test]
+    fn accept_language() {
+        let cases = [
+            (None, Language::English),
+            (Some("fa-IR,fa;q=0.9,en;q=0.8"), Language::Persian),
+            (Some("en-US,en;q=0.9,fa;q=0.8"), Language::English),
+            (Some("de-DE,fa;q=0.5"), Language::Persian),
+            (Some("en;q=0.2, fa;q=0.7"), Language::Persian),
+            (Some("de-DE"), Language::English),
+        ];
+        for (header, language) in cases {
+            assert_eq!(
+                Language::from_accept_language(header.map(String::from)),
+                language,
+                "{header:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// ingredients share one csv column: "cheese;tomato;basil"
+const INGREDIENT_SEPARATOR: char = ';';
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl MenuFormat {
+    pub fn from_param(format: Option<&str>) -> Result<Self, Error> {
+        match format.map(|f| f.to_lowercase()).as_deref() {
+            None | Some("json") => Ok(MenuFormat::Json),
+            Some("csv") => Ok(MenuFormat::Csv),
+            Some(_) => Err(Error::parse_error),
+        }
+    }
+
+    // used by the cli when `--format` isn't given
+    pub fn from_path(path: &str) -> Self {
+        match path.to_lowercase().ends_with(".csv") {
+            true => MenuFormat::Csv,
+            false => MenuFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MenuFormat::Json => "json",
+            MenuFormat::Csv => "csv",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            MenuFormat::Json => "application/json",
+            MenuFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+fn default_available() -> bool {
+    true
+}
+
+// one food of an imported/exported menu
+// rows with an `id` update that food, rows without one are matched by name or created
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MenuRow {
+    #[serde(default)]
+    pub id: Option<i32>,
+    pub name: String,
+    #[serde(default)]
+    pub image: String,
+    pub tag: String,
+    pub price: i32,
+    #[serde(default)]
+    pub ingredient: Vec<String>,
+    #[serde(default = "default_available")]
+    pub available: bool,
+}
+
+// csv can't hold a list, otherwise the same as `MenuRow`
+#[derive(Serialize, Deserialize)]
+struct CsvMenuRow {
+    #[serde(default)]
+    id: Option<i32>,
+    name: String,
+    #[serde(default)]
+    image: String,
+    tag: String,
+    price: i32,
+    #[serde(default)]
+    ingredient: String,
+    #[serde(default = "default_available")]
+    available: bool,
+}
+
+impl From<CsvMenuRow> for MenuRow {
+    fn from(row: CsvMenuRow) -> Self {
+        MenuRow {
+            id: row.id,
+            name: row.name,
+            image: row.image,
+            tag: row.tag,
+            price: row.price,
+            ingredient: row
+                .ingredient
+                .split(INGREDIENT_SEPARATOR)
+                .map(str::trim)
+                .filter(|i| !i.is_empty())
+                .map(String::from)
+                .collect(),
+            available: row.available,
+        }
+    }
+}
+
+impl From<&MenuRow> for CsvMenuRow {
+    fn from(row: &MenuRow) -> Self {
+        CsvMenuRow {
+            id: row.id,
+            name: row.name.clone(),
+            image: row.image.clone(),
+            tag: row.tag.clone(),
+            price: row.price,
+            ingredient: row.ingredient.join(&INGREDIENT_SEPARATOR.to_string()),
+            available: row.available,
+        }
+    }
+}
+
+impl MenuRow {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("name is empty".to_string());
+        }
+        if self.tag.trim().is_empty() {
+            errors.push("tag is empty".to_string());
+        }
+        if self.price < 0 {
+            errors.push("price can't be negative".to_string());
+        }
+        if self.ingredient.iter().any(|i| i.contains(INGREDIENT_SEPARATOR)) {
+            errors.push(format!("ingredients can't contain `{INGREDIENT_SEPARATOR}`"));
+        }
+        errors
+    }
+}
+
+// `row` starts at 1 for the first food, the csv header isn't counted
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    // false when it was a dry run or any row had an error, nothing is written then
+    pub applied: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub errors: Vec<RowError>,
+}
+
+// what a valid row turns into
+#[derive(Debug, Clone)]
+pub enum MenuChange {
+    Create(MenuRow),
+    Update(i32, MenuRow),
+}
+
+// every row is parsed on its own, so one broken row doesn't hide the others
+pub fn parse_menu(format: MenuFormat, body: &[u8]) -> Result<Vec<Result<MenuRow, RowError>>, Error> {
+    match format {
+        MenuFormat::Json => {
+            let values = serde_json::from_slice::<Vec<serde_json::Value>>(body)
+                .map_err(|e| Error::invalid_menu_file(e.to_string()))?;
+            Ok(values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    serde_json::from_value::<MenuRow>(value).map_err(|e| RowError {
+                        row: i + 1,
+                        message: e.to_string(),
+                    })
+                })
+                .collect())
+        }
+        MenuFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(body);
+            reader
+                .headers()
+                .map_err(|e| Error::invalid_menu_file(e.to_string()))?;
+            Ok(reader
+                .deserialize::<CsvMenuRow>()
+                .enumerate()
+                .map(|(i, row)| {
+                    row.map(MenuRow::from).map_err(|e| RowError {
+                        row: i + 1,
+                        message: e.to_string(),
+                    })
+                })
+                .collect())
+        }
+    }
+}
+
+pub fn write_menu(format: MenuFormat, rows: &[MenuRow]) -> Result<Vec<u8>, Error> {
+    match format {
+        MenuFormat::Json => {
+            serde_json::to_vec_pretty(rows).map_err(|e| Error::invalid_menu_file(e.to_string()))
+        }
+        MenuFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer
+                    .serialize(CsvMenuRow::from(row))
+                    .map_err(|e| Error::invalid_menu_file(e.to_string()))?;
+            }
+            writer
+                .into_inner()
+                .map_err(|e| Error::invalid_menu_file(e.to_string()))
+        }
+    }
+}
+
+// matches the rows against the current menu (`id -> name`) and collects every problem
+pub fn plan_import(
+    existing: &HashMap<i32, String>,
+    rows: Vec<Result<MenuRow, RowError>>,
+) -> (Vec<MenuChange>, Vec<RowError>) {
+    let by_name: HashMap<&str, i32> = existing
+        .iter()
+        .map(|(id, name)| (name.as_str(), *id))
+        .collect();
+    let mut changes = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen_ids = HashSet::new();
+    let mut seen_names = HashSet::new();
+
+    for (i, row) in rows.into_iter().enumerate() {
+        let row_number = i + 1;
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        let mut row_errors = row.validate();
+
+        let target = match row.id {
+            Some(id) if !existing.contains_key(&id) => {
+                row_errors.push(format!("food {id} doesn't belong to this restaurant"));
+                None
+            }
+            Some(id) => Some(id),
+            None => by_name.get(row.name.trim()).copied(),
+        };
+        if let Some(id) = target {
+            if !seen_ids.insert(id) {
+                row_errors.push(format!("food {id} appears more than once"));
+            }
+        }
+        if !seen_names.insert(row.name.trim().to_string()) {
+            row_errors.push(format!("`{}` appears more than once", row.name.trim()));
+        }
+
+        if row_errors.is_empty() {
+            changes.push(match target {
+                Some(id) => MenuChange::Update(id, row),
+                None => MenuChange::Create(row),
+            });
+        } else {
+            errors.extend(row_errors.into_iter().map(|message| RowError {
+                row: row_number,
+                message,
+            }));
+        }
+    }
+    (changes, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> Vec<MenuRow> {
+        vec![
+            MenuRow {
+                id: Some(1),
+                name: "Ghormeh Sabzi".to_string(),
+                image: "https://example.com/ghormeh.jpg".to_string(),
+                tag: "iranian".to_string(),
+                price: 250000,
+                ingredient: vec!["herbs".to_string(), "kidney beans".to_string(), "lamb".to_string()],
+                available: true,
+            },
+            MenuRow {
+                id: Some(2),
+                name: "Doogh, large".to_string(),
+                image: String::new(),
+                tag: "drinks".to_string(),
+                price: 40000,
+                ingredient: Vec::new(),
+                available: false,
+            },
+        ]
+    }
+
+    fn existing() -> HashMap<i32, String> {
+        HashMap::from([(1, "Ghormeh Sabzi".to_string()), (2, "Doogh, large".to_string())])
+    }
+
+    fn round_trip(format: MenuFormat) {
+        let rows = menu();
+        let file = write_menu(format, &rows).unwrap();
+        let parsed: Vec<MenuRow> = parse_menu(format, &file)
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(parsed, rows);
+
+        let (changes, errors) = plan_import(&existing(), parsed.into_iter().map(Ok).collect());
+        assert!(errors.is_empty(), "{errors:?}");
+        let updated: Vec<(i32, MenuRow)> = changes
+            .into_iter()
+            .map(|change| match change {
+                MenuChange::Update(id, row) => (id, row),
+                MenuChange::Create(row) => panic!("`{}` would be created again", row.name),
+            })
+            .collect();
+        assert_eq!(updated, rows.into_iter().map(|row| (row.id.unwrap(), row)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        round_trip(MenuFormat::Json);
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        round_trip(MenuFormat::Csv);
+    }
+
+    #[test]
+    fn rows_without_id_match_by_name() {
+        let csv = "name,tag,price\nGhormeh Sabzi,iranian,260000\nKashk Bademjan,iranian,180000\n";
+        let rows = parse_menu(MenuFormat::Csv, csv.as_bytes()).unwrap();
+        let (changes, errors) = plan_import(&existing(), rows);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(matches!(&changes[0], MenuChange::Update(1, row) if row.price == 260000 && row.available));
+        assert!(matches!(&changes[1], MenuChange::Create(row) if row.name == "Kashk Bademjan"));
+    }
+
+    #[test]
+    fn csv_errors_are_reported_per_row() {
+        let csv = "id,name,tag,price,ingredient\n\
+            1,Ghormeh Sabzi,iranian,250000,\n\
+            ,Doogh,drinks,cheap,\n\
+            ,Kabab,,-5,\n\
+            9,Tahdig,iranian,90000,\n\
+            ,Ghormeh Sabzi,iranian,250000,\n";
+        let rows = parse_menu(MenuFormat::Csv, csv.as_bytes()).unwrap();
+        let (changes, errors) = plan_import(&existing(), rows);
+        assert_eq!(changes.len(), 1);
+
+        let messages = |row: usize| -> Vec<&str> {
+            errors.iter().filter(|e| e.row == row).map(|e| e.message.as_str()).collect()
+        };
+        assert!(messages(1).is_empty());
+        assert_eq!(messages(2).len(), 1, "price isn't a number");
+        assert_eq!(
+            messages(3),
+            ["tag is empty", "price can't be negative"]
+        );
+        assert_eq!(messages(4), ["food 9 doesn't belong to this restaurant"]);
+        assert_eq!(
+            messages(5),
+            ["food 1 appears more than once", "`Ghormeh Sabzi` appears more than once"]
+        );
+    }
+
+    #[test]
+    fn json_errors_are_reported_per_row() {
+        let json = r#"[
+            { "name": "Ghormeh Sabzi", "tag": "iranian", "price": 250000 },
+            { "name": "Doogh", "price": 40000 },
+            { "name": "Kabab", "tag": "iranian", "price": -1 }
+        ]"#;
+        let rows = parse_menu(MenuFormat::Json, json.as_bytes()).unwrap();
+        let (changes, errors) = plan_import(&existing(), rows);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row, 2);
+        assert!(errors[0].message.contains("tag"), "{}", errors[0].message);
+        assert_eq!(errors[1].row, 3);
+        assert_eq!(errors[1].message, "price can't be negative");
+    }
+
+    #[test]
+    fn old_discount_columns_are_ignored() {
+        let csv = "id,name,image,tag,price,discount,discount_price,ingredient,available\n\
+            3,Margherita,url,pizza,300000,true,250000,cheese;tomato,true\n";
+        let rows = parse_menu(MenuFormat::Csv, csv.as_bytes()).unwrap();
+        let row = rows.into_iter().next().unwrap().unwrap();
+        assert_eq!(row.price, 300000);
+        assert_eq!(row.ingredient, ["cheese", "tomato"]);
+    }
+
+    #[test]
+    fn broken_file_is_rejected_whole() {
+        assert!(matches!(
+            parse_menu(MenuFormat::Json, b"{ not a list"),
+            Err(Error::invalid_menu_file(_))
+        ));
+    }
+}
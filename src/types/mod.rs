@@ -1,7 +1,25 @@
+pub mod availability;
+pub mod bundle;
 pub mod comment;
+pub mod delivery;
+pub mod dietary;
+pub mod favorite;
 pub mod food;
+pub mod food_option;
+pub mod food_search;
+pub mod gallery;
+pub mod language;
+pub mod menu;
 pub mod pagination;
+pub mod price_history;
+pub mod promotion;
+pub mod recommendation;
 pub mod restaurant;
+pub mod review;
+pub mod section;
+pub mod stats;
+pub mod stock;
+pub mod tag;
 pub mod timer;
 pub mod account;
 pub mod owner;
@@ -3,7 +3,14 @@ use serde::{Deserialize, Serialize};
 use sqlx::prelude::Type;
 use uuid::Uuid;
 
+use super::{
+  bundle::{ItemComponent, SlotChoice},
+  food_option::ItemOption,
+};
 
+
+// `GetOrder` is what the API sends, this one mirrors the `orders` table
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Order {
   pub id: i32,
@@ -14,47 +21,93 @@ pub struct Order {
   pub created_at: NaiveDateTime,
 }
 
-// user adds a food to his cart:
-// we make a cart and set the status to "cart"
-// user paid? set the status to "pending"
-// restaurant served the food? set the status to "completed" -> should be done by restaurant manually
-// if user cancelled the order after payment it's up to restaurant to resolve the order. in that case restaurant must set the status to "canceled"
+// user adds a food to his cart: we make a cart and set the status to "cart"
+// checkout turns it into "pending", then the restaurant moves it along:
+//   pending -> accepted -> preparing -> ready -> out_for_delivery -> completed
+// a ready order can also be completed right away (picked up)
+// the customer can cancel while it's pending, the restaurant until it's ready
+// an order out for delivery is completed by the system after `AUTO_COMPLETE_AFTER_MINUTES` if nobody did
+// every change is kept in `order_status_history`
 // we can use this data to ban a user if "canceled" orders reach a limit!
-// frontend can send status as "completed" after a period if it wasnt done by restaurant
 
-#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Type)]
 #[sqlx(type_name = "text")]
+#[sqlx(rename_all = "snake_case")]
 pub enum OrderStatus {
     Cart,
     Pending,
+    Accepted,
+    Preparing,
+    Ready,
+    OutForDelivery,
     Completed,
     Canceled,
 }
 
+pub const AUTO_COMPLETE_AFTER_MINUTES: i32 = 120;
+
+// who changed the status, `system` is the server itself (checkout has the customer as actor)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Type)]
+#[sqlx(type_name = "text")]
+#[sqlx(rename_all = "lowercase")]
+#[allow(non_camel_case_types)]
+pub enum StatusActor {
+    customer,
+    owner,
+    system,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OrderStatusChange {
+  pub id: i32,
+  pub order_id: i32,
+  pub from_status: OrderStatus,
+  pub to_status: OrderStatus,
+  pub actor: StatusActor,
+  pub actor_id: Option<Uuid>,
+  pub changed_at: NaiveDateTime,
+}
+
+// body of `PUT /order/{id}/status`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewOrderStatus {
+  pub status: OrderStatus,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Item {
   pub id: i32,
   pub order_id: i32,
   pub restaurant_id: i32,
-  pub food_id: i32,
+  // one of `food_id` and `bundle_id` is set, neither when the bundle was deleted after the order
+  pub food_id: Option<i32>,
+  pub bundle_id: Option<i32>,
   pub quantity: i32,
   pub account_id: Uuid,
   pub name: String,
   pub image: String,
   pub price: i32,
-  pub discount_price: Option<i32>
+  pub discount_price: Option<i32>,
+  // sum of the options' `price_delta`, added to `price` and `discount_price`
+  pub options_price: i32,
+  pub options: Vec<ItemOption>,
+  // the foods of a bundle, what the kitchen prepares
+  pub components: Vec<ItemComponent>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+// everything else (the cart, restaurant, name, image and price) is looked up on the server
 pub struct NewItem {
-  pub order_id: i32,
-  pub restaurant_id: i32,
-  pub food_id: i32,
+  // either a food or a bundle
+  pub food_id: Option<i32>,
+  pub bundle_id: Option<i32>,
   pub quantity: i32,
-  pub name: String,
-  pub image: String,
-  pub price: i32,
-  pub discount_price: Option<i32>
+  // ids of the chosen options, the same food with other options is another item
+  #[serde(default)]
+  pub options: Vec<i32>,
+  // a food for every slot of the bundle, the same bundle with other choices is another item
+  #[serde(default)]
+  pub choices: Vec<SlotChoice>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -63,6 +116,7 @@ pub struct GetOrder {
   pub account_id: Uuid,
   pub order_id: i32,
   pub status: OrderStatus,
+  pub delivery_fee: i32,
   pub total_price: i32,
   pub total_discounted_price: i32,
 }
@@ -73,6 +127,10 @@ impl OrderStatus {
     match self {
         Self::Cart => "cart",
         Self::Pending => "pending",
+        Self::Accepted => "accepted",
+        Self::Preparing => "preparing",
+        Self::Ready => "ready",
+        Self::OutForDelivery => "out_for_delivery",
         Self::Completed => "completed",
         Self::Canceled => "canceled",
     }
@@ -82,9 +140,91 @@ impl OrderStatus {
     match s.to_lowercase().as_str() {
         "cart" => Some(OrderStatus::Cart),
         "pending" => Some(OrderStatus::Pending),
+        "accepted" => Some(OrderStatus::Accepted),
+        "preparing" => Some(OrderStatus::Preparing),
+        "ready" => Some(OrderStatus::Ready),
+        "out_for_delivery" => Some(OrderStatus::OutForDelivery),
         "completed" => Some(OrderStatus::Completed),
         "canceled" => Some(OrderStatus::Canceled),
         _ => None,  
     }
 }
-}
\ No newline at end of file
+
+  // the moves `PUT /order/{id}/status` allows, cart -> pending only happens through checkout
+  pub fn can_change_to(self, to: OrderStatus, actor: StatusActor) -> bool {
+    use OrderStatus::*;
+    match actor {
+        StatusActor::customer => matches!((self, to), (Pending, Canceled)),
+        StatusActor::owner => matches!(
+          (self, to),
+          (Pending, Accepted)
+            | (Accepted, Preparing)
+            | (Preparing, Ready)
+            | (Ready, OutForDelivery)
+            | (Ready, Completed)
+            | (OutForDelivery, Completed)
+            | (Pending | Accepted | Preparing, Canceled)
+        ),
+        StatusActor::system => matches!((self, to), (OutForDelivery, Completed)),
+    }
+  }
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use OrderStatus::*;
+
+  const ALL: [OrderStatus; 8] = [Cart, Pending, Accepted, Preparing, Ready, OutForDelivery, Completed, Canceled];
+
+  // every pair outside `allowed` has to be refused
+  fn check(actor: StatusActor, allowed: &[(OrderStatus, OrderStatus)]) {
+    for from in ALL {
+      for to in ALL {
+        assert_eq!(
+          from.can_change_to(to, actor),
+          allowed.contains(&(from, to)),
+          "{from:?} -> {to:?} by {actor:?}"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn customer_can_only_cancel_a_pending_order() {
+    check(StatusActor::customer, &[(Pending, Canceled)]);
+  }
+
+  #[test]
+  fn owner_moves_the_order_along_and_cancels_before_it_is_ready() {
+    check(
+      StatusActor::owner,
+      &[
+        (Pending, Accepted),
+        (Accepted, Preparing),
+        (Preparing, Ready),
+        (Ready, OutForDelivery),
+        (Ready, Completed),
+        (OutForDelivery, Completed),
+        (Pending, Canceled),
+        (Accepted, Canceled),
+        (Preparing, Canceled),
+      ],
+    );
+  }
+
+  #[test]
+  fn system_only_completes_deliveries() {
+    check(StatusActor::system, &[(OutForDelivery, Completed)]);
+  }
+
+  #[test]
+  fn nothing_leaves_a_final_status_or_goes_back_to_the_cart() {
+    for actor in [StatusActor::customer, StatusActor::owner, StatusActor::system] {
+      for status in ALL {
+        assert!(!Completed.can_change_to(status, actor));
+        assert!(!Canceled.can_change_to(status, actor));
+        assert!(!status.can_change_to(Cart, actor));
+      }
+    }
+  }
+}
@@ -5,6 +5,8 @@ use super::orders::OrderStatus;
 
 
 
+// the `payment` table, nothing pays through the API yet
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Payment {
   pub id: i32,
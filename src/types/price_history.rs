@@ -0,0 +1,12 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// the food's price from `changed_at` until the next entry
+// promotions aren't part of it, see `types::promotion`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PriceChange {
+    pub id: i32,
+    pub food_id: i32,
+    pub price: i32,
+    pub changed_at: NaiveDateTime,
+}
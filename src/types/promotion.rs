@@ -0,0 +1,336 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::Type;
+
+use crate::error::Error;
+
+use super::{
+    food::Food,
+    restaurant::{is_open_between, next_time_of_day, Weekday},
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Type)]
+#[sqlx(type_name = "text")]
+#[sqlx(rename_all = "lowercase")]
+#[allow(non_camel_case_types)]
+pub enum DiscountKind {
+    // `value` percent off, 1..=100
+    percent,
+    // `value` off the price, never below 0
+    fixed,
+}
+
+// a discount that runs by itself ("20% off from 12:00 to 15:00 this week")
+// the target is one food, a managed tag, a menu section or, when none is set, the whole restaurant
+// `starts_at`/`ends_at` bound it in time, `days` and `window_from`/`window_to` repeat it every week
+// a window can pass midnight, it then belongs to the day it starts on
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Promotion {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub name: String,
+    pub kind: DiscountKind,
+    pub value: i32,
+    pub food_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub section_id: Option<i32>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub days: Option<Vec<Weekday>>,
+    pub window_from: Option<NaiveTime>,
+    pub window_to: Option<NaiveTime>,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewPromotion {
+    pub name: String,
+    pub kind: DiscountKind,
+    pub value: i32,
+    pub food_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub section_id: Option<i32>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub days: Option<Vec<Weekday>>,
+    pub window_from: Option<NaiveTime>,
+    pub window_to: Option<NaiveTime>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl NewPromotion {
+    pub fn validate(&self) -> Result<(), Error> {
+        let targets = [self.food_id, self.tag_id, self.section_id]
+            .iter()
+            .filter(|target| target.is_some())
+            .count();
+        if self.name.trim().is_empty() {
+            Err(Error::invalid_field("name is empty".to_string()))
+        } else if self.kind == DiscountKind::percent && !(1..=100).contains(&self.value) {
+            Err(Error::invalid_field("a percent discount is between 1 and 100".to_string()))
+        } else if self.kind == DiscountKind::fixed && self.value < 1 {
+            Err(Error::invalid_field("a fixed discount must be positive".to_string()))
+        } else if targets > 1 {
+            Err(Error::invalid_field(
+                "only one of food_id, tag_id and section_id can be set".to_string(),
+            ))
+        } else if matches!((self.starts_at, self.ends_at), (Some(from), Some(to)) if from >= to) {
+            Err(Error::invalid_field("starts_at must be before ends_at".to_string()))
+        } else if self.days.as_ref().is_some_and(|days| days.is_empty()) {
+            Err(Error::invalid_field("days can't be empty, leave it out for every day".to_string()))
+        } else {
+            match (self.window_from, self.window_to) {
+                (None, None) => Ok(()),
+                (Some(from), Some(to)) if from != to => Ok(()),
+                (Some(_), Some(_)) => Err(Error::invalid_field(
+                    "window_from and window_to can't be the same".to_string(),
+                )),
+                _ => Err(Error::invalid_field(
+                    "window_from and window_to go together".to_string(),
+                )),
+            }
+        }
+    }
+}
+
+impl Promotion {
+    pub fn is_running_at(&self, now: NaiveDateTime) -> bool {
+        if !self.active
+            || self.starts_at.is_some_and(|starts_at| now < starts_at)
+            || self.ends_at.is_some_and(|ends_at| now >= ends_at)
+        {
+            return false;
+        }
+        let time = now.time();
+        let today = Weekday::from(now.weekday());
+        let day = match (self.window_from, self.window_to) {
+            (Some(from), Some(to)) => {
+                if !is_open_between(from, to, time) {
+                    return false;
+                }
+                // the part of an overnight window after midnight belongs to the day before
+                if from > to && time < to {
+                    today.previous()
+                } else {
+                    today
+                }
+            }
+            _ => today,
+        };
+        self.days.as_ref().is_none_or(|days| days.contains(&day))
+    }
+
+    // the next moment `is_running_at` may give another answer, `None` when it never will
+    // a window with `days` can only change at its ends, `days` alone at midnight
+    pub fn next_change_after(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.active || self.ends_at.is_some_and(|ends_at| ends_at <= now) {
+            return None;
+        }
+        let mut changes: Vec<NaiveDateTime> = self
+            .starts_at
+            .filter(|starts_at| *starts_at > now)
+            .into_iter()
+            .chain(self.ends_at)
+            .collect();
+        match (self.window_from, self.window_to) {
+            (Some(from), Some(to)) => {
+                changes.push(next_time_of_day(now, from));
+                changes.push(next_time_of_day(now, to));
+            }
+            _ if self.days.is_some() => changes.push(next_time_of_day(now, NaiveTime::MIN)),
+            _ => {}
+        }
+        changes.into_iter().min()
+    }
+
+    pub fn applies_to(&self, food: &Food) -> bool {
+        food.restaurant_id == self.restaurant_id
+            && match (self.food_id, self.tag_id, self.section_id) {
+                (Some(food_id), _, _) => food.id == food_id,
+                (_, Some(tag_id), _) => food.tag_id == Some(tag_id),
+                (_, _, Some(section_id)) => food.section_id == Some(section_id),
+                _ => true,
+            }
+    }
+
+    pub fn discounted(&self, price: i32) -> i32 {
+        match self.kind {
+            // in i64, prices in rials times 100 don't fit an i32
+            DiscountKind::percent => price - (price as i64 * self.value as i64 / 100) as i32,
+            DiscountKind::fixed => (price - self.value).max(0),
+        }
+    }
+}
+
+// sets `effective_price` and `promotion_id` of the foods
+// the best running promotion on `price` wins
+pub fn apply_promotions(foods: &mut [Food], promotions: &[Promotion], now: NaiveDateTime) {
+    let running: Vec<&Promotion> = promotions
+        .iter()
+        .filter(|promotion| promotion.is_running_at(now))
+        .collect();
+    for food in foods {
+        let mut best = (food.price, None);
+        for promotion in running.iter().filter(|promotion| promotion.applies_to(food)) {
+            let price = promotion.discounted(food.price);
+            if price < best.0 {
+                best = (price, Some(promotion.id));
+            }
+        }
+        (food.effective_price, food.promotion_id) = best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    use crate::types::food::test_food;
+
+    // 2025-03-07 is a Friday
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn promotion() -> Promotion {
+        Promotion {
+            id: 1,
+            restaurant_id: 1,
+            name: "Lunch".to_string(),
+            kind: DiscountKind::percent,
+            value: 20,
+            food_id: None,
+            tag_id: None,
+            section_id: None,
+            starts_at: None,
+            ends_at: None,
+            days: None,
+            window_from: None,
+            window_to: None,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn runs_between_start_and_end() {
+        let promotion = Promotion {
+            starts_at: Some(at(7, 12, 0)),
+            ends_at: Some(at(8, 12, 0)),
+            ..promotion()
+        };
+        assert!(!promotion.is_running_at(at(7, 11, 59)));
+        assert!(promotion.is_running_at(at(7, 12, 0)));
+        assert!(promotion.is_running_at(at(8, 11, 59)));
+        assert!(!promotion.is_running_at(at(8, 12, 0)));
+    }
+
+    #[test]
+    fn inactive_never_runs() {
+        let promotion = Promotion {
+            active: false,
+            ..promotion()
+        };
+        assert!(!promotion.is_running_at(at(7, 12, 0)));
+        assert_eq!(promotion.next_change_after(at(7, 12, 0)), None);
+    }
+
+    #[test]
+    fn daytime_window_on_its_days() {
+        let promotion = Promotion {
+            days: Some(vec![Weekday::Friday]),
+            window_from: Some(time(12)),
+            window_to: Some(time(15)),
+            ..promotion()
+        };
+        assert!(!promotion.is_running_at(at(7, 11, 0)));
+        assert!(promotion.is_running_at(at(7, 13, 0)));
+        assert!(!promotion.is_running_at(at(7, 15, 0)));
+        // Saturday
+        assert!(!promotion.is_running_at(at(8, 13, 0)));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_the_day_it_starts() {
+        let promotion = Promotion {
+            days: Some(vec![Weekday::Friday]),
+            window_from: Some(time(22)),
+            window_to: Some(time(2)),
+            ..promotion()
+        };
+        assert!(promotion.is_running_at(at(7, 23, 0)));
+        // Saturday night, started on Friday
+        assert!(promotion.is_running_at(at(8, 1, 0)));
+        assert!(!promotion.is_running_at(at(8, 23, 0)));
+        // Friday night, started on Thursday
+        assert!(!promotion.is_running_at(at(7, 1, 0)));
+    }
+
+    #[test]
+    fn next_change_is_the_closest_bound() {
+        let window = Promotion {
+            window_from: Some(time(12)),
+            window_to: Some(time(15)),
+            ..promotion()
+        };
+        assert_eq!(window.next_change_after(at(7, 13, 0)), Some(at(7, 15, 0)));
+        assert_eq!(window.next_change_after(at(7, 16, 0)), Some(at(8, 12, 0)));
+        let days = Promotion {
+            days: Some(vec![Weekday::Friday]),
+            starts_at: Some(at(9, 0, 0)),
+            ..promotion()
+        };
+        assert_eq!(days.next_change_after(at(7, 13, 0)), Some(at(8, 0, 0)));
+        let ended = Promotion {
+            ends_at: Some(at(7, 12, 0)),
+            ..promotion()
+        };
+        assert_eq!(ended.next_change_after(at(7, 13, 0)), None);
+        assert_eq!(promotion().next_change_after(at(7, 13, 0)), None);
+    }
+
+    #[test]
+    fn percent_of_a_large_price_does_not_overflow() {
+        let promotion = Promotion {
+            value: 50,
+            ..promotion()
+        };
+        assert_eq!(promotion.discounted(2_000_000_000), 1_000_000_000);
+    }
+
+    #[test]
+    fn best_running_promotion_wins() {
+        let mut foods = vec![test_food(1, 1000), test_food(2, 1000)];
+        let promotions = vec![
+            promotion(),
+            Promotion {
+                id: 2,
+                kind: DiscountKind::fixed,
+                value: 300,
+                food_id: Some(2),
+                ..promotion()
+            },
+            Promotion {
+                id: 3,
+                value: 90,
+                active: false,
+                ..promotion()
+            },
+        ];
+        apply_promotions(&mut foods, &promotions, at(7, 12, 0));
+        assert_eq!((foods[0].effective_price, foods[0].promotion_id), (800, Some(1)));
+        assert_eq!((foods[1].effective_price, foods[1].promotion_id), (700, Some(2)));
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use super::restaurant::Restaurant;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Recommendation {
+    #[serde(flatten)]
+    pub restaurant: Restaurant,
+    // only useful for ordering, the scale may change
+    pub score: f64,
+}
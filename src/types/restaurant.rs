@@ -1,9 +1,13 @@
 use std::fmt;
 
-use chrono::NaiveTime;
-use serde::{Deserialize, Serialize};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use sqlx::prelude::Type;
 
+use crate::{error::Error, utils::etag::hash_etag};
+
+use super::language::Language;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Restaurant {
     pub id: RestaurantId,
@@ -11,10 +15,18 @@ pub struct Restaurant {
     pub rating: f64,
     pub distance: f64,
     pub tags: Option<Vec<String>>,
+    // managed tags, see `types::tag`. `null` on update keeps the current tags
+    pub tag_ids: Option<Vec<i32>>,
     pub image: String,
     pub address: String,
     pub city: String,
     pub location: [f64; 2],
+    // only sent for authenticated requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_favorite: Option<bool>,
+    // read only, writes are checked with `If-Match` instead
+    #[serde(default)]
+    pub version: i32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, Hash, PartialEq)]
@@ -30,6 +42,28 @@ impl fmt::Display for Restaurant {
     }
 }
 
+impl Restaurant {
+    // `rating` is computed from the comments and isn't checked
+    pub fn validate(&self) -> Result<(), Error> {
+        let [lat, lon] = self.location;
+        if self.name.trim().is_empty() {
+            Err(Error::invalid_field("name is empty".to_string()))
+        } else if self.city.trim().is_empty() {
+            Err(Error::invalid_field("city is empty".to_string()))
+        } else if self.address.trim().is_empty() {
+            Err(Error::invalid_field("address is empty".to_string()))
+        } else if !self.distance.is_finite() || self.distance < 0.0 {
+            Err(Error::invalid_field("distance can't be negative".to_string()))
+        } else if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            Err(Error::invalid_field("location must be [lat, lon]".to_string()))
+        } else if self.tags.iter().flatten().any(|t| t.trim().is_empty()) {
+            Err(Error::invalid_field("tags can't be empty".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 impl fmt::Display for RestaurantId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -42,55 +76,569 @@ pub struct NewRestaurant {
     pub rating: f64,
     pub distance: f64,
     pub tags: Option<Vec<String>>,
+    pub tag_ids: Option<Vec<i32>>,
     pub image: String,
     pub address: String,
     pub city: String,
     pub location: [f64; 2],
 }
 
+// a restaurant can have several non-overlapping intervals per day (lunch and dinner)
 #[derive(Serialize, Deserialize, Clone, Debug)]
-
 pub struct OpenHours {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub day_of_week: Weekday,
+    pub open_time: NaiveTime,
+    pub close_time: NaiveTime,
+    #[serde(default)]
+    pub version: i32,
+}
+
+// hours as sent to the client, `day_of_week` follows the `Accept-Language` header
+#[derive(Serialize, Clone, Debug)]
+pub struct LocalizedOpenHours {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub day_of_week: &'static str,
+    pub open_time: NaiveTime,
+    pub close_time: NaiveTime,
+    pub version: i32,
+}
+
+impl OpenHours {
+    pub fn localized(&self, language: Language) -> LocalizedOpenHours {
+        LocalizedOpenHours {
+            id: self.id,
+            restaurant_id: self.restaurant_id,
+            day_of_week: self.day_of_week.label(language),
+            open_time: self.open_time,
+            close_time: self.close_time,
+            version: self.version,
+        }
+    }
+
+    // the ETag of a whole week, it changes when any interval is added, changed or removed
+    pub fn week_etag(week: &[OpenHours]) -> String {
+        let mut versions: Vec<(i32, i32)> = week.iter().map(|h| (h.id, h.version)).collect();
+        versions.sort_unstable();
+        let key: String = versions
+            .iter()
+            .map(|(id, version)| format!("{id}:{version};"))
+            .collect();
+        hash_etag(key.as_bytes())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewOpenHours {
     pub restaurant_id: i32,
     pub day_of_week: Weekday,
     pub open_time: NaiveTime,
     pub close_time: NaiveTime,
 }
-#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+
+// one entry of the weekly schedule, used by the bulk `PUT /restaurants/{id}/hours`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HoursInterval {
+    pub day_of_week: Weekday,
+    pub open_time: NaiveTime,
+    pub close_time: NaiveTime,
+}
+
+const SECONDS_IN_DAY: u32 = 24 * 60 * 60;
+
+impl HoursInterval {
+    // (day, start, end) in seconds from midnight
+    // an interval that closes after midnight is split over its own day and the next one
+    fn segments(&self) -> Vec<(Weekday, u32, u32)> {
+        let open = self.open_time.num_seconds_from_midnight();
+        let close = self.close_time.num_seconds_from_midnight();
+        if open < close {
+            vec![(self.day_of_week.clone(), open, close)]
+        } else {
+            vec![
+                (self.day_of_week.clone(), open, SECONDS_IN_DAY),
+                (self.day_of_week.next(), 0, close),
+            ]
+        }
+    }
+}
+
+impl From<&OpenHours> for HoursInterval {
+    fn from(hours: &OpenHours) -> Self {
+        HoursInterval {
+            day_of_week: hours.day_of_week.clone(),
+            open_time: hours.open_time,
+            close_time: hours.close_time,
+        }
+    }
+}
+
+// rejects empty intervals and any two intervals that share a moment,
+// including overnight hours spilling into the next day
+pub fn validate_week(intervals: &[HoursInterval]) -> Result<(), Error> {
+    if intervals.iter().any(|i| i.open_time == i.close_time) {
+        return Err(Error::invalid_hours);
+    }
+    let segments: Vec<(Weekday, u32, u32)> =
+        intervals.iter().flat_map(HoursInterval::segments).collect();
+    for (i, a) in segments.iter().enumerate() {
+        for b in &segments[i + 1..] {
+            if a.0 == b.0 && a.1 < b.2 && b.1 < a.2 {
+                return Err(Error::overlapping_hours);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn is_open_at(intervals: &[HoursInterval], day: &Weekday, time: NaiveTime) -> bool {
+    let time = time.num_seconds_from_midnight();
+    intervals
+        .iter()
+        .flat_map(HoursInterval::segments)
+        .any(|(d, start, end)| &d == day && start <= time && time < end)
+}
+
+// date based overrides of the weekly schedule (holidays, special nights, temporary closures)
+// a single day is stored with start_date == end_date
+// if `closed` is false, open_time and close_time replace the weekly hours for those dates
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HoursException {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub closed: bool,
+    pub open_time: Option<NaiveTime>,
+    pub close_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewHoursException {
+    pub restaurant_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub closed: bool,
+    pub open_time: Option<NaiveTime>,
+    pub close_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+// the same rules as the table's CHECKs, so a bad exception is a 400 and not a database error
+fn validate_exception(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    closed: bool,
+    open_time: Option<NaiveTime>,
+    close_time: Option<NaiveTime>,
+) -> Result<(), Error> {
+    if end_date < start_date {
+        return Err(Error::invalid_field("end_date can't be before start_date".to_string()));
+    }
+    match (closed, open_time, close_time) {
+        (true, _, _) => Ok(()),
+        (false, Some(open_time), Some(close_time)) if open_time == close_time => Err(Error::invalid_hours),
+        (false, Some(_), Some(_)) => Ok(()),
+        (false, _, _) => Err(Error::invalid_field(
+            "open_time and close_time are required unless closed".to_string(),
+        )),
+    }
+}
+
+impl NewHoursException {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_exception(self.start_date, self.end_date, self.closed, self.open_time, self.close_time)
+    }
+}
+
+impl HoursException {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_exception(self.start_date, self.end_date, self.closed, self.open_time, self.close_time)
+    }
+}
+
+// the intervals that apply on `date`: the exception's when there is one (none if it closes the
+// day), otherwise the weekly ones of that day. overnight hours still spill into the next day
+pub fn intervals_on(
+    date: NaiveDate,
+    exception: Option<&HoursException>,
+    week: &[HoursInterval],
+) -> Vec<HoursInterval> {
+    let day = Weekday::from(date.weekday());
+    match exception {
+        Some(exception) => match (exception.closed, exception.open_time, exception.close_time) {
+            (false, Some(open_time), Some(close_time)) => vec![HoursInterval {
+                day_of_week: day,
+                open_time,
+                close_time,
+            }],
+            _ => Vec::new(),
+        },
+        None => week.iter().filter(|i| i.day_of_week == day).cloned().collect(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpenStatus {
+    pub restaurant_id: i32,
+    pub is_open: bool,
+    pub reason: Option<String>,
+}
+
+// close_time before open_time means the restaurant closes after midnight
+pub fn is_open_between(open_time: NaiveTime, close_time: NaiveTime, time: NaiveTime) -> bool {
+    if open_time <= close_time {
+        time >= open_time && time < close_time
+    } else {
+        time >= open_time || time < close_time
+    }
+}
+
+// the next time the clock shows `time`, today or tomorrow
+pub fn next_time_of_day(now: NaiveDateTime, time: NaiveTime) -> NaiveDateTime {
+    let today = now.date().and_time(time);
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+// the stored representation is the Persian label of the `weekday` enum in postgres (plain space, no ZWNJ)
+// the JSON representation is the English name, `Weekday::label` renders either language
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, Hash, Type)]
 #[sqlx(type_name = "weekday")]
 pub enum Weekday {
+    #[sqlx(rename = "شنبه")]
     Saturday,
+    #[sqlx(rename = "یکشنبه")]
     Sunday,
+    #[sqlx(rename = "دوشنبه")]
     Monday,
+    #[sqlx(rename = "سه شنبه")]
     Tuesday,
+    #[sqlx(rename = "چهارشنبه")]
     Wednesday,
+    #[sqlx(rename = "پنجشنبه")]
     Thursday,
+    #[sqlx(rename = "جمعه")]
     Friday,
 }
 
+// accepts the English names and every Persian spelling `_from_str` knows about
+impl<'de> Deserialize<'de> for Weekday {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let day = String::deserialize(deserializer)?;
+        Weekday::_from_str(&day)
+            .ok_or_else(|| de::Error::custom(format!("unknown day of week: {day}")))
+    }
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(day: chrono::Weekday) -> Self {
+        match day {
+            chrono::Weekday::Sat => Self::Saturday,
+            chrono::Weekday::Sun => Self::Sunday,
+            chrono::Weekday::Mon => Self::Monday,
+            chrono::Weekday::Tue => Self::Tuesday,
+            chrono::Weekday::Wed => Self::Wednesday,
+            chrono::Weekday::Thu => Self::Thursday,
+            chrono::Weekday::Fri => Self::Friday,
+        }
+    }
+}
+
 impl Weekday {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Saturday => Self::Sunday,
+            Self::Sunday => Self::Monday,
+            Self::Monday => Self::Tuesday,
+            Self::Tuesday => Self::Wednesday,
+            Self::Wednesday => Self::Thursday,
+            Self::Thursday => Self::Friday,
+            Self::Friday => Self::Saturday,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            Self::Saturday => Self::Friday,
+            Self::Sunday => Self::Saturday,
+            Self::Monday => Self::Sunday,
+            Self::Tuesday => Self::Monday,
+            Self::Wednesday => Self::Tuesday,
+            Self::Thursday => Self::Wednesday,
+            Self::Friday => Self::Thursday,
+        }
+    }
+
+    // canonical stored label, must match the `weekday` enum in 0v1_schema.sql
     pub fn _to_str(&self) -> &'static str {
         match self {
             Self::Saturday => "شنبه",
             Self::Sunday => "یکشنبه",
             Self::Monday => "دوشنبه",
-            Self::Tuesday => "سه‌ شنبه",
+            Self::Tuesday => "سه شنبه",
             Self::Wednesday => "چهارشنبه",
             Self::Thursday => "پنجشنبه",
             Self::Friday => "جمعه",
         }
     }
 
+    pub fn english(&self) -> &'static str {
+        match self {
+            Self::Saturday => "Saturday",
+            Self::Sunday => "Sunday",
+            Self::Monday => "Monday",
+            Self::Tuesday => "Tuesday",
+            Self::Wednesday => "Wednesday",
+            Self::Thursday => "Thursday",
+            Self::Friday => "Friday",
+        }
+    }
+
+    pub fn label(&self, language: Language) -> &'static str {
+        match language {
+            Language::English => self.english(),
+            Language::Persian => self._to_str(),
+        }
+    }
+
+    // persian days are typed with a plain space, a ZWNJ, both, or nothing between the parts
+    // and some keyboards send arabic yeh/kaf, so we compare the normalized form
     pub fn _from_str(day: &str) -> Option<Self> {
-        match day {
-            "شنبه" => Some(Self::Saturday),
-            "یکشنبه" | "یک شنبه" => Some(Self::Sunday),
-            "دوشنبه" | "دو شنبه" => Some(Self::Monday),
-            "سه‌ شنبه" | "سه‌شنبه" => Some(Self::Tuesday),
-            "چهارشنبه" | "چهار شنبه" => Some(Self::Wednesday),
-            "پنجشنبه" | "پنج شنبه" => Some(Self::Thursday),
-            "جمعه" => Some(Self::Friday),
+        let day: String = day
+            .trim()
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '\u{200c}')
+            .map(|c| match c {
+                'ي' | 'ى' => 'ی',
+                'ك' => 'ک',
+                c => c,
+            })
+            .collect::<String>()
+            .to_lowercase();
+
+        match day.as_str() {
+            "شنبه" | "saturday" => Some(Self::Saturday),
+            "یکشنبه" | "sunday" => Some(Self::Sunday),
+            "دوشنبه" | "monday" => Some(Self::Monday),
+            "سهشنبه" | "tuesday" => Some(Self::Tuesday),
+            "چهارشنبه" | "wednesday" => Some(Self::Wednesday),
+            "پنجشنبه" | "thursday" => Some(Self::Thursday),
+            "جمعه" | "friday" => Some(Self::Friday),
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::initial_sql::init_sql;
+
+    const WEEK: [Weekday; 7] = [
+        Weekday::Saturday,
+        Weekday::Sunday,
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+    ];
+
+    #[test]
+    fn stored_label_round_trip() {
+        for day in WEEK {
+            assert_eq!(Weekday::_from_str(day._to_str()), Some(day));
+        }
+    }
+
+    #[test]
+    fn stored_labels_match_schema_enum() {
+        let schema = init_sql();
+        let start = schema.find("CREATE TYPE weekday AS ENUM (").unwrap();
+        let end = start + schema[start..].find(')').unwrap();
+        let labels: Vec<&str> = schema[start..end].split('\'').skip(1).step_by(2).collect();
+        let stored: Vec<&str> = WEEK.iter().map(Weekday::_to_str).collect();
+        assert_eq!(labels, stored);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        for day in WEEK {
+            let json = serde_json::to_string(&day).unwrap();
+            assert_eq!(json, format!("\"{}\"", day.english()));
+            assert_eq!(serde_json::from_str::<Weekday>(&json).unwrap(), day);
+        }
+    }
+
+    #[test]
+    fn persian_json_is_accepted() {
+        for day in WEEK {
+            let json = format!("\"{}\"", day.label(Language::Persian));
+            assert_eq!(serde_json::from_str::<Weekday>(&json).unwrap(), day);
+        }
+    }
+
+    #[test]
+    fn persian_aliases() {
+        let aliases = [
+            ("یک شنبه", Weekday::Sunday),
+            ("دو شنبه", Weekday::Monday),
+            ("سه\u{200c}شنبه", Weekday::Tuesday),
+            ("سه\u{200c} شنبه", Weekday::Tuesday),
+            ("سهشنبه", Weekday::Tuesday),
+            ("چهار شنبه", Weekday::Wednesday),
+            ("پنج\u{200c}شنبه", Weekday::Thursday),
+            ("يكشنبه", Weekday::Sunday),
+        ];
+        for (alias, day) in aliases {
+            assert_eq!(Weekday::_from_str(alias), Some(day), "{alias}");
+        }
+        assert_eq!(Weekday::_from_str("someday"), None);
+    }
+
+    fn interval(day: Weekday, open: &str, close: &str) -> HoursInterval {
+        HoursInterval {
+            day_of_week: day,
+            open_time: open.parse().unwrap(),
+            close_time: close.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn split_day_is_valid() {
+        let week = [
+            interval(Weekday::Monday, "12:00:00", "15:00:00"),
+            interval(Weekday::Monday, "19:00:00", "23:00:00"),
+            interval(Weekday::Tuesday, "12:00:00", "15:00:00"),
+        ];
+        assert!(validate_week(&week).is_ok());
+        assert!(validate_week(&[]).is_ok());
+    }
+
+    #[test]
+    fn touching_intervals_are_valid() {
+        let week = [
+            interval(Weekday::Monday, "12:00:00", "15:00:00"),
+            interval(Weekday::Monday, "15:00:00", "18:00:00"),
+            interval(Weekday::Monday, "20:00:00", "02:00:00"),
+            interval(Weekday::Tuesday, "02:00:00", "10:00:00"),
+        ];
+        assert!(validate_week(&week).is_ok());
+    }
+
+    #[test]
+    fn overlapping_intervals_are_rejected() {
+        let week = [
+            interval(Weekday::Monday, "12:00:00", "15:00:00"),
+            interval(Weekday::Monday, "14:00:00", "18:00:00"),
+        ];
+        assert!(matches!(validate_week(&week), Err(Error::overlapping_hours)));
+
+        let nested = [
+            interval(Weekday::Friday, "10:00:00", "22:00:00"),
+            interval(Weekday::Friday, "12:00:00", "13:00:00"),
+        ];
+        assert!(matches!(validate_week(&nested), Err(Error::overlapping_hours)));
+    }
+
+    #[test]
+    fn overnight_spill_is_checked_against_next_day() {
+        let week = [
+            interval(Weekday::Friday, "20:00:00", "03:00:00"),
+            interval(Weekday::Saturday, "02:00:00", "10:00:00"),
+        ];
+        assert!(matches!(validate_week(&week), Err(Error::overlapping_hours)));
+
+        let week_wrap = [
+            interval(Weekday::Friday, "20:00:00", "03:00:00"),
+            interval(Weekday::Saturday, "03:00:00", "10:00:00"),
+        ];
+        assert!(validate_week(&week_wrap).is_ok());
+    }
+
+    #[test]
+    fn empty_interval_is_rejected() {
+        let week = [interval(Weekday::Monday, "12:00:00", "12:00:00")];
+        assert!(matches!(validate_week(&week), Err(Error::invalid_hours)));
+    }
+
+    #[test]
+    fn next_time_of_day_is_today_or_tomorrow() {
+        let now = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let later = NaiveTime::from_hms_opt(15, 0, 0).unwrap();
+        let earlier = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(next_time_of_day(now, later), now.date().and_time(later));
+        assert_eq!(next_time_of_day(now, earlier), now.date().succ_opt().unwrap().and_time(earlier));
+        assert_eq!(next_time_of_day(now, now.time()), now + Duration::days(1));
+    }
+
+    fn exception(date: &str, closed: bool, hours: Option<(&str, &str)>) -> HoursException {
+        HoursException {
+            id: 1,
+            restaurant_id: 1,
+            start_date: date.parse().unwrap(),
+            end_date: date.parse().unwrap(),
+            closed,
+            open_time: hours.map(|(open, _)| open.parse().unwrap()),
+            close_time: hours.map(|(_, close)| close.parse().unwrap()),
+            reason: None,
+        }
+    }
+
+    // open at `date time` with the exceptions of that day and the day before
+    fn open_at(week: &[HoursInterval], exceptions: &[HoursException], date: &str, time: &str) -> bool {
+        let date: NaiveDate = date.parse().unwrap();
+        let yesterday = date - Duration::days(1);
+        let on = |day: NaiveDate| exceptions.iter().find(|e| e.start_date <= day && day <= e.end_date);
+        let mut intervals = intervals_on(yesterday, on(yesterday), week);
+        intervals.extend(intervals_on(date, on(date), week));
+        is_open_at(&intervals, &Weekday::from(date.weekday()), time.parse().unwrap())
+    }
+
+    #[test]
+    fn closed_day_takes_its_overnight_hours_along() {
+        // 2025-03-07 is a Friday
+        let week = [interval(Weekday::Friday, "18:00:00", "02:00:00")];
+        assert!(open_at(&week, &[], "2025-03-08", "01:00:00"));
+        let closed = [exception("2025-03-07", true, None)];
+        assert!(!open_at(&week, &closed, "2025-03-08", "01:00:00"));
+    }
+
+    #[test]
+    fn special_hours_keep_yesterdays_overnight_hours() {
+        let week = [interval(Weekday::Friday, "18:00:00", "02:00:00")];
+        let special = [exception("2025-03-08", false, Some(("12:00:00", "15:00:00")))];
+        assert!(open_at(&week, &special, "2025-03-08", "01:00:00"));
+        assert!(open_at(&week, &special, "2025-03-08", "13:00:00"));
+        assert!(!open_at(&week, &special, "2025-03-08", "19:00:00"));
+    }
+
+    #[test]
+    fn overnight_special_hours_spill_into_the_next_day() {
+        let special = [exception("2025-12-21", false, Some(("20:00:00", "03:00:00")))];
+        assert!(open_at(&[], &special, "2025-12-22", "02:00:00"));
+        assert!(!open_at(&[], &special, "2025-12-22", "04:00:00"));
+    }
+
+    #[test]
+    fn exceptions_are_validated() {
+        let mut backwards = exception("2025-03-07", true, None);
+        backwards.end_date = "2025-03-06".parse().unwrap();
+        assert!(matches!(backwards.validate(), Err(Error::invalid_field(_))));
+        assert!(matches!(exception("2025-03-07", false, None).validate(), Err(Error::invalid_field(_))));
+        let empty = exception("2025-03-07", false, Some(("12:00:00", "12:00:00")));
+        assert!(matches!(empty.validate(), Err(Error::invalid_hours)));
+        assert!(exception("2025-03-07", true, None).validate().is_ok());
+        assert!(exception("2025-03-07", false, Some(("20:00:00", "03:00:00"))).validate().is_ok());
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::Error;
+
+// one review per account and food, only for foods of the account's completed orders
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FoodReview {
+    pub id: i32,
+    pub food_id: i32,
+    pub account_id: Uuid,
+    pub name: String,
+    pub rating: i32,
+    pub text: String,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewFoodReview {
+    pub rating: i32,
+    #[serde(default)]
+    pub text: String,
+}
+
+impl NewFoodReview {
+    pub fn validate(&self) -> Result<(), Error> {
+        if (1..=5).contains(&self.rating) {
+            Ok(())
+        } else {
+            Err(Error::invalid_field("rating is between 1 and 5".to_string()))
+        }
+    }
+}
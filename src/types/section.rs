@@ -0,0 +1,123 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+use super::{bundle::Bundle, food::Food, food_option::MenuFood};
+
+// a heading of the menu ("Starters", "Kebabs", "Drinks"), shown in `sort_order`
+// `available_from`/`available_to` limit it to a time of day (e.g. breakfast), both or neither are set
+// and like open hours a window can pass midnight
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MenuSection {
+    pub id: i32,
+    pub restaurant_id: i32,
+    pub name: String,
+    pub sort_order: i32,
+    pub available_from: Option<NaiveTime>,
+    pub available_to: Option<NaiveTime>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewMenuSection {
+    pub name: String,
+    pub available_from: Option<NaiveTime>,
+    pub available_to: Option<NaiveTime>,
+}
+
+impl NewMenuSection {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::invalid_field("name is empty".to_string()));
+        }
+        match (self.available_from, self.available_to) {
+            (None, None) => Ok(()),
+            (Some(from), Some(to)) if from != to => Ok(()),
+            (Some(_), Some(_)) => Err(Error::invalid_field(
+                "available_from and available_to can't be the same".to_string(),
+            )),
+            _ => Err(Error::invalid_field(
+                "available_from and available_to go together".to_string(),
+            )),
+        }
+    }
+}
+
+// `?sort=loved` puts the best reviewed foods of every section first, unreviewed ones keep the menu order after them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MenuSort {
+    #[default]
+    Menu,
+    Loved,
+}
+
+impl MenuSort {
+    pub fn from_param(sort: Option<&str>) -> Result<Self, Error> {
+        match sort.map(|s| s.to_lowercase()).as_deref() {
+            None | Some("menu") => Ok(MenuSort::Menu),
+            Some("loved") => Ok(MenuSort::Loved),
+            Some(_) => Err(Error::parse_error),
+        }
+    }
+
+    pub fn sort(self, foods: &mut [Food]) {
+        if self == MenuSort::Loved {
+            foods.sort_by(|a, b| {
+                (b.rating_count > 0)
+                    .cmp(&(a.rating_count > 0))
+                    .then(b.rating.total_cmp(&a.rating))
+                    .then(b.rating_count.cmp(&a.rating_count))
+            });
+        }
+    }
+}
+
+// one heading of `GET /restaurants/{id}/food`, foods without a section come last with `section: null`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MenuGroup {
+    pub section: Option<MenuSection>,
+    pub foods: Vec<MenuFood>,
+    pub bundles: Vec<Bundle>,
+}
+
+impl MenuGroup {
+    // `sections`, `foods` and `bundles` are expected in display order, empty sections are left out
+    pub fn group(sections: Vec<MenuSection>, foods: Vec<MenuFood>, bundles: Vec<Bundle>) -> Vec<MenuGroup> {
+        let mut groups: Vec<MenuGroup> = sections
+            .into_iter()
+            .map(|section| MenuGroup {
+                section: Some(section),
+                foods: Vec::new(),
+                bundles: Vec::new(),
+            })
+            .collect();
+        let mut unsectioned = MenuGroup {
+            section: None,
+            foods: Vec::new(),
+            bundles: Vec::new(),
+        };
+        for food in foods {
+            let group = groups.iter_mut().find(|group| {
+                group.section.as_ref().map(|section| section.id) == food.food.section_id
+            });
+            match group {
+                Some(group) => group.foods.push(food),
+                None => unsectioned.foods.push(food),
+            }
+        }
+        for bundle in bundles {
+            let group = groups.iter_mut().find(|group| {
+                group.section.as_ref().map(|section| section.id) == bundle.section_id
+            });
+            match group {
+                Some(group) => group.bundles.push(bundle),
+                None => unsectioned.bundles.push(bundle),
+            }
+        }
+        groups.retain(|group| !group.foods.is_empty() || !group.bundles.is_empty());
+        if !unsectioned.foods.is_empty() || !unsectioned.bundles.is_empty() {
+            groups.push(unsectioned);
+        }
+        groups
+    }
+}
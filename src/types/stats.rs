@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    #[default]
+    Day,
+    Week,
+}
+
+impl Bucket {
+    // the `date_trunc` / `generate_series` unit
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+        }
+    }
+}
+
+// both ends are inclusive, weeks start on monday
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatsRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub bucket: Bucket,
+}
+
+// `?from=2025-01-01&to=2025-01-31&bucket=week`, every parameter is optional
+// the default range is the last 30 days
+pub fn extract_stats_range(params: &HashMap<String, String>) -> Result<StatsRange, Error> {
+    let parse_date = |key: &str| {
+        params
+            .get(key)
+            .map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| Error::parse_error))
+            .transpose()
+    };
+    let to = parse_date("to")?.unwrap_or_else(|| Local::now().date_naive());
+    let from = parse_date("from")?.unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+    let bucket = match params.get("bucket").map(String::as_str) {
+        None | Some("day") => Bucket::Day,
+        Some("week") => Bucket::Week,
+        Some(_) => return Err(Error::parse_error),
+    };
+    if from > to || (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(Error::invalid_date_range);
+    }
+    Ok(StatsRange { from, to, bucket })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SalesPoint {
+    // first day of the bucket
+    pub period: NaiveDate,
+    pub orders: i64,
+    pub canceled_orders: i64,
+    pub revenue: i64,
+}
+
+// every checked out order that isn't `canceled` counts as a sale, whatever its status, carts are ignored
+// revenue is the food total (discount applied) without delivery fees
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SalesStats {
+    #[serde(flatten)]
+    pub range: StatsRange,
+    pub orders: i64,
+    pub canceled_orders: i64,
+    pub revenue: i64,
+    pub average_basket: f64,
+    // canceled / (sales + canceled), 0 when there were no orders
+    pub cancellation_rate: f64,
+    pub series: Vec<SalesPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopFood {
+    pub food_id: i32,
+    pub name: String,
+    pub quantity: i64,
+    pub orders: i64,
+    pub revenue: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RatingPoint {
+    pub period: NaiveDate,
+    pub comments: i64,
+    // null for buckets without comments
+    pub average_rating: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RatingStats {
+    #[serde(flatten)]
+    pub range: StatsRange,
+    pub comments: i64,
+    pub average_rating: Option<f64>,
+    pub series: Vec<RatingPoint>,
+}
@@ -0,0 +1,40 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// optional daily stock of a food, checkout takes the ordered quantity from `remaining`
+// every day at `reset_time` (server time) `remaining` goes back to `daily_quantity`
+// `sold_out` is set when the stock made the food unavailable, the reset makes it available again
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FoodStock {
+    pub food_id: i32,
+    pub name: String,
+    pub available: bool,
+    pub daily_quantity: i32,
+    pub remaining: i32,
+    pub reset_time: NaiveTime,
+    pub last_reset: NaiveDateTime,
+    pub sold_out: bool,
+}
+
+// `remaining` defaults to `daily_quantity`, send it alone to adjust today's stock
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewFoodStock {
+    pub daily_quantity: i32,
+    pub remaining: Option<i32>,
+    #[serde(default)]
+    pub reset_time: NaiveTime,
+}
+
+impl NewFoodStock {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.daily_quantity < 0 || self.remaining.is_some_and(|remaining| remaining < 0) {
+            Err(Error::invalid_field(
+                "daily_quantity and remaining can't be negative".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// managed tags replace the free text `restaurant.tags` and `food.tag`
+// restaurants link to tags through `restaurant_tag`, foods through `food.tag_id`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Tag {
+    pub id: i32,
+    pub slug: String,
+    pub label_fa: String,
+    pub label_en: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewTag {
+    pub slug: String,
+    pub label_fa: String,
+    pub label_en: String,
+}
+
+impl NewTag {
+    // the slug is checked after `slugify`, a slug of only whitespace is empty
+    pub fn validate(&self) -> Result<(), Error> {
+        if slugify(&self.slug).is_empty() {
+            Err(Error::invalid_field("slug is empty".to_string()))
+        } else if self.label_fa.trim().is_empty() {
+            Err(Error::invalid_field("label_fa is empty".to_string()))
+        } else if self.label_en.trim().is_empty() {
+            Err(Error::invalid_field("label_en is empty".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TagCount {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub restaurant_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TagMigration {
+    pub tags_created: u64,
+    pub restaurants_linked: u64,
+    pub foods_linked: u64,
+}
+
+// same rule as the migration in `Store::migrate_free_text_tags`: trimmed, lowercase, whitespace -> '-'
+pub fn slugify(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("-")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(slug: &str, label_fa: &str, label_en: &str) -> NewTag {
+        NewTag {
+            slug: slug.to_string(),
+            label_fa: label_fa.to_string(),
+            label_en: label_en.to_string(),
+        }
+    }
+
+    #[test]
+    fn empty_slugs_and_labels_are_rejected() {
+        assert!(tag("Fast Food", "فست فود", "Fast food").validate().is_ok());
+        assert!(matches!(tag("   ", "فست فود", "Fast food").validate(), Err(Error::invalid_field(_))));
+        assert!(matches!(tag("fast-food", " ", "Fast food").validate(), Err(Error::invalid_field(_))));
+        assert!(matches!(tag("fast-food", "فست فود", "").validate(), Err(Error::invalid_field(_))));
+    }
+}
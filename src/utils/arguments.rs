@@ -1,4 +1,4 @@
-use clap::{command, Arg, ArgAction, ArgMatches};
+use clap::{command, value_parser, Arg, ArgAction, ArgMatches, Command};
 
 pub fn arguments() -> ArgMatches {
     command!()
@@ -26,5 +26,70 @@ pub fn arguments() -> ArgMatches {
                 .help("inserts fake data into database")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            // --migrate-tags
+            Arg::new("migrate-tags")
+                .long("migrate-tags")
+                .help("turns free text restaurant/food tags into managed tags")
+                .action(ArgAction::SetTrue),
+        )
+        // --cache-menu 600
+        .arg(cache_arg("cache-restaurants", "GET /restaurants and GET /restaurants/{id}", "60"))
+        .arg(cache_arg("cache-menu", "GET /restaurants/{id}/food", "300"))
+        .arg(cache_arg("cache-hours", "GET /restaurants/hours/{id}", "300"))
+        // subcommands run once and exit, the server isn't started
+        .subcommand(
+            // import-menu -r 1 menu.csv --dry-run
+            Command::new("import-menu")
+                .about("imports a csv or json menu into a restaurant, all rows or nothing")
+                .arg(restaurant_arg())
+                .arg(Arg::new("file").required(true).help("path of the menu file"))
+                .arg(format_arg())
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("only validates the file and prints the report")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            // export-menu -r 1 -o menu.csv
+            Command::new("export-menu")
+                .about("exports the menu of a restaurant as csv or json")
+                .arg(restaurant_arg())
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("file to write, prints to stdout when missing"),
+                )
+                .arg(format_arg()),
+        )
         .get_matches()
 }
+
+fn restaurant_arg() -> Arg {
+    Arg::new("restaurant")
+        .short('r')
+        .long("restaurant")
+        .required(true)
+        .value_parser(value_parser!(i32))
+        .help("restaurant id")
+}
+
+fn format_arg() -> Arg {
+    Arg::new("format")
+        .long("format")
+        .value_parser(["csv", "json"])
+        .help("csv or json, guessed from the file name when missing")
+}
+
+// Cache-Control max-age of a public route
+fn cache_arg(name: &'static str, route: &'static str, default: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("SECONDS")
+        .value_parser(value_parser!(u32))
+        .default_value(default)
+        .help(format!("Cache-Control max-age of {route}, 0 makes clients revalidate every time"))
+}
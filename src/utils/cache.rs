@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use clap::ArgMatches;
+use serde::Serialize;
+use warp::{
+    http::{header, HeaderValue, Response, StatusCode},
+    hyper::{body::Bytes, Body},
+    Filter, Rejection,
+};
+
+use crate::{error::Error, types::language::Language};
+
+use super::etag::hash_etag;
+
+// writes that don't go through this process (e.g. `import-menu` from the command line)
+// are picked up after this long at most
+const ENTRY_TTL_SECONDS: i64 = 300;
+// the list keys depend on the query string, past this the cache starts over
+const MAX_ENTRIES: usize = 1024;
+
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CacheKey {
+    Restaurants { limit: Option<i32>, offset: i32 },
+    Restaurant(i32),
+    Menu(i32),
+    Hours(i32, Language),
+}
+
+impl CacheKey {
+    fn restaurant_id(&self) -> Option<i32> {
+        match self {
+            CacheKey::Restaurants { .. } => None,
+            CacheKey::Restaurant(id) | CacheKey::Menu(id) | CacheKey::Hours(id, _) => Some(*id),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub body: Bytes,
+    pub etag: String,
+    // when the body last changed as far as the cache knows, an entry rebuilt with the same
+    // etag keeps the time of the one it replaces
+    pub last_modified: DateTime<Utc>,
+    // the entry's age is counted from here
+    built_at: DateTime<Utc>,
+    // the body goes stale at this time by itself (a promotion starts, a window closes)
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl CachedResponse {
+    // the etag is a hash of the body unless the resource has its own (e.g. a version)
+    pub fn new<T: Serialize>(value: &T, etag: Option<String>) -> Result<Self, Error> {
+        let body = serde_json::to_vec(value).map_err(|_| Error::parse_error)?;
+        let now = Utc::now();
+        Ok(CachedResponse {
+            etag: etag.unwrap_or_else(|| hash_etag(&body)),
+            body: Bytes::from(body),
+            last_modified: now,
+            built_at: now,
+            expires_at: None,
+        })
+    }
+
+    pub fn expiring_at(mut self, expires_at: Option<DateTime<Utc>>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
+
+    // clients mustn't keep the body past `expires_at` either
+    pub fn max_age(&self, max_age: u32) -> u32 {
+        match self.expires_at {
+            Some(expires_at) => (expires_at - Utc::now()).num_seconds().clamp(0, max_age as i64) as u32,
+            None => max_age,
+        }
+    }
+}
+
+// `max-age` of each public route, in seconds
+// 0 means clients have to revalidate every time (they still get a 304)
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    pub restaurants: u32,
+    pub menu: u32,
+    pub hours: u32,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        CachePolicy {
+            restaurants: 60,
+            menu: 300,
+            hours: 300,
+        }
+    }
+}
+
+impl CachePolicy {
+    pub fn from_arguments(arguments: &ArgMatches) -> Self {
+        let default = CachePolicy::default();
+        CachePolicy {
+            restaurants: arguments
+                .get_one::<u32>("cache-restaurants")
+                .copied()
+                .unwrap_or(default.restaurants),
+            menu: arguments.get_one::<u32>("cache-menu").copied().unwrap_or(default.menu),
+            hours: arguments.get_one::<u32>("cache-hours").copied().unwrap_or(default.hours),
+        }
+    }
+
+    pub fn cache_control(max_age: u32) -> String {
+        match max_age {
+            0 => "public, no-cache".to_string(),
+            max_age => format!("public, max-age={max_age}"),
+        }
+    }
+}
+
+// responses that depend on the caller (`is_favorite`) never go into a shared cache
+pub const PRIVATE_CACHE_CONTROL: &str = "private, no-store";
+
+// in-process cache of the public read endpoints
+// the store clears the entries of a restaurant whenever it changes its rows
+#[derive(Debug, Clone, Default)]
+pub struct ResponseCache {
+    entries: Arc<RwLock<HashMap<CacheKey, CachedResponse>>>,
+    pub policy: CachePolicy,
+}
+
+impl ResponseCache {
+    pub fn new(policy: CachePolicy) -> Self {
+        ResponseCache {
+            entries: Arc::default(),
+            policy,
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|entry| {
+                let now = Utc::now();
+                now - entry.built_at < Duration::seconds(ENTRY_TTL_SECONDS)
+                    && entry.expires_at.is_none_or(|expires_at| now < expires_at)
+            })
+            .cloned()
+    }
+
+    pub fn insert(&self, key: CacheKey, mut response: CachedResponse) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = entries.get(&key).filter(|previous| previous.etag == response.etag) {
+            response.last_modified = previous.last_modified;
+        }
+        if entries.len() >= MAX_ENTRIES {
+            entries.clear();
+        }
+        entries.insert(key, response);
+    }
+
+    // the restaurant row changed: its own entries and every list
+    pub fn invalidate_restaurant(&self, restaurant_id: i32) {
+        self.retain(|key| {
+            key.restaurant_id()
+                .is_some_and(|id| id != restaurant_id)
+        });
+    }
+
+    // something only the lists show changed, e.g. a new restaurant
+    pub fn invalidate_lists(&self) {
+        self.retain(|key| !matches!(key, CacheKey::Restaurants { .. }));
+    }
+
+    pub fn invalidate_menu(&self, restaurant_id: i32) {
+        self.retain(|key| *key != CacheKey::Menu(restaurant_id));
+    }
+
+    pub fn invalidate_hours(&self, restaurant_id: i32) {
+        self.retain(|key| !matches!(key, CacheKey::Hours(id, _) if *id == restaurant_id));
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    fn retain(&self, keep: impl Fn(&CacheKey) -> bool) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|key, _| keep(key));
+    }
+}
+
+// `If-None-Match` / `If-Modified-Since` of a GET
+#[derive(Debug, Clone, Default)]
+pub struct Conditional {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+}
+
+impl Conditional {
+    // `If-Modified-Since` is ignored when `If-None-Match` is sent (RFC 9110)
+    pub fn not_modified(&self, response: &CachedResponse) -> bool {
+        if let Some(if_none_match) = &self.if_none_match {
+            return if_none_match.split(',').map(str::trim).any(|tag| {
+                tag == "*" || tag.trim_start_matches("W/") == response.etag
+            });
+        }
+        match self
+            .if_modified_since
+            .as_deref()
+            .and_then(|since| NaiveDateTime::parse_from_str(since.trim(), HTTP_DATE).ok())
+        {
+            Some(since) => response.last_modified.timestamp() <= since.and_utc().timestamp(),
+            None => false,
+        }
+    }
+}
+
+pub fn conditional() -> impl Filter<Extract = (Conditional,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("if-none-match")
+        .and(warp::header::optional::<String>("if-modified-since"))
+        .map(|if_none_match, if_modified_since| Conditional {
+            if_none_match,
+            if_modified_since,
+        })
+}
+
+// 304 without a body when the client's copy is still good, the json otherwise
+pub fn cached_reply(
+    response: &CachedResponse,
+    conditional: &Conditional,
+    cache_control: &str,
+) -> Response<Body> {
+    let mut reply = if conditional.not_modified(response) {
+        let mut reply = Response::new(Body::empty());
+        *reply.status_mut() = StatusCode::NOT_MODIFIED;
+        reply
+    } else {
+        let mut reply = Response::new(Body::from(response.body.clone()));
+        reply
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        reply
+    };
+
+    let headers = reply.headers_mut();
+    if let Ok(etag) = HeaderValue::from_str(&response.etag) {
+        headers.insert(header::ETAG, etag);
+    }
+    if let Ok(last_modified) =
+        HeaderValue::from_str(&response.last_modified.format(HTTP_DATE).to_string())
+    {
+        headers.insert(header::LAST_MODIFIED, last_modified);
+    }
+    if let Ok(cache_control) = HeaderValue::from_str(cache_control) {
+        headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    reply
+}
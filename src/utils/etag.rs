@@ -0,0 +1,56 @@
+use warp::{Filter, Rejection};
+
+use crate::error::Error;
+
+// etag of a versioned row (restaurant, food, hours)
+pub fn version_etag(version: i32) -> String {
+    format!("\"{version}\"")
+}
+
+// etag of something without a single version, e.g. a list of rows
+// FNV-1a, so the value doesn't change between builds or restarts
+pub fn hash_etag(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("\"{hash:016x}\"")
+}
+
+#[derive(Debug, Clone)]
+pub enum IfMatch {
+    // `If-Match: *`
+    Any,
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    // writes can't go through without the header
+    pub fn parse(header: Option<String>) -> Result<Self, Error> {
+        let header = header.ok_or(Error::precondition_required)?;
+        if header.trim() == "*" {
+            return Ok(IfMatch::Any);
+        }
+        Ok(IfMatch::Tags(
+            header
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        ))
+    }
+
+    // strong comparison, a weak `W/"..."` tag never matches
+    pub fn check(&self, etag: &str) -> Result<(), Error> {
+        match self {
+            IfMatch::Any => Ok(()),
+            IfMatch::Tags(tags) if tags.iter().any(|tag| tag == etag) => Ok(()),
+            IfMatch::Tags(_) => Err(Error::precondition_failed),
+        }
+    }
+}
+
+pub fn if_match() -> impl Filter<Extract = (IfMatch,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("if-match").and_then(|header: Option<String>| async move {
+        IfMatch::parse(header).map_err(warp::reject::custom)
+    })
+}
@@ -0,0 +1,77 @@
+use clap::ArgMatches;
+
+use crate::{
+    error::Error,
+    store::Store,
+    types::menu::{parse_menu, write_menu, ImportReport, MenuFormat},
+    utils::colors::ansi::*,
+};
+
+// runs `import-menu` / `export-menu`, returns false when the command failed
+pub async fn run(store: &Store, command: &str, matches: &ArgMatches) -> bool {
+    let result = match command {
+        "import-menu" => import_menu(store, matches).await,
+        "export-menu" => export_menu(store, matches).await,
+        _ => return false,
+    };
+    match result {
+        Ok(ok) => ok,
+        Err(e) => {
+            eprintln!("{BRIGHT_RED}{command} failed:{RESET} {e}");
+            false
+        }
+    }
+}
+
+fn format(matches: &ArgMatches, path: Option<&String>) -> MenuFormat {
+    match matches.get_one::<String>("format") {
+        Some(format) => MenuFormat::from_param(Some(format)).unwrap_or_default(),
+        None => path.map(|p| MenuFormat::from_path(p)).unwrap_or_default(),
+    }
+}
+
+async fn import_menu(store: &Store, matches: &ArgMatches) -> Result<bool, Error> {
+    let restaurant_id = *matches.get_one::<i32>("restaurant").expect("required");
+    let path = matches.get_one::<String>("file").expect("required");
+    let dry_run = matches.get_flag("dry-run");
+
+    let body = std::fs::read(path).map_err(|e| Error::invalid_menu_file(e.to_string()))?;
+    let rows = parse_menu(format(matches, Some(path)), &body)?;
+    let report = store.import_menu(restaurant_id, rows, dry_run).await?;
+    print_report(&report);
+    Ok(report.errors.is_empty())
+}
+
+async fn export_menu(store: &Store, matches: &ArgMatches) -> Result<bool, Error> {
+    let restaurant_id = *matches.get_one::<i32>("restaurant").expect("required");
+    let output = matches.get_one::<String>("output");
+
+    let rows = store.export_menu(restaurant_id).await?;
+    let file = write_menu(format(matches, output), &rows)?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, file).map_err(Error::write_file)?;
+            println!("{BRIGHT_GREEN}{} foods exported to {path}{RESET}", rows.len());
+        }
+        None => println!("{}", String::from_utf8_lossy(&file)),
+    }
+    Ok(true)
+}
+
+fn print_report(report: &ImportReport) {
+    for error in &report.errors {
+        println!("{BRIGHT_RED}row {}:{RESET} {}", error.row, error.message);
+    }
+    let summary = format!(
+        "{} rows, {} to create, {} to update, {} errors",
+        report.rows,
+        report.created,
+        report.updated,
+        report.errors.len()
+    );
+    match (report.applied, report.dry_run) {
+        (true, _) => println!("{BRIGHT_GREEN}Menu imported:{RESET} {summary}"),
+        (false, true) => println!("{BRIGHT_YELLOW}Dry run:{RESET} {summary}"),
+        (false, false) => println!("{BRIGHT_RED}Nothing imported:{RESET} {summary}"),
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+
+// merge patches are small, anything bigger is a mistake
+pub const PATCH_BODY_LIMIT: u64 = 64 * 1024;
+
+// RFC 7396: objects are merged key by key, `null` removes a key, anything else replaces the value
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!()
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+// parses a merge patch body, it has to be a json object
+pub fn parse_patch(body: &[u8]) -> Result<Map<String, Value>, Error> {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(patch)) => Ok(patch),
+        Ok(_) => Err(Error::invalid_patch("the patch must be a json object".to_string())),
+        Err(e) => Err(Error::invalid_patch(e.to_string())),
+    }
+}
+
+// applies `patch` to a copy of `current`
+// removing a required field (`"name": null`) or sending a wrong type fails while deserializing
+pub fn apply_patch<T: Serialize + DeserializeOwned>(
+    current: &T,
+    patch: &Map<String, Value>,
+    read_only: &[&str],
+) -> Result<T, Error> {
+    if let Some(field) = read_only.iter().find(|f| patch.contains_key(**f)) {
+        return Err(Error::invalid_patch(format!("`{field}` can't be changed")));
+    }
+    let mut target = serde_json::to_value(current).map_err(|e| Error::invalid_patch(e.to_string()))?;
+    merge_patch(&mut target, &Value::Object(patch.clone()));
+    serde_json::from_value(target).map_err(|e| Error::invalid_field(e.to_string()))
+}
@@ -1,4 +1,8 @@
 pub mod arguments;
+pub mod cache;
 pub mod initial_sql;
+pub mod merge_patch;
+pub mod menu_cli;
 pub mod colors;
+pub mod etag;
 pub mod upload_file;
@@ -63,3 +63,17 @@ pub async fn upload_file(file_type: String, mut form: FormData) -> Result<String
     
     Ok(public_url)
 }
+
+// only files stored by `upload_file` (`/files/...`) are removed, anything else is left alone
+pub async fn delete_file(public_url: &str) -> Result<(), Error> {
+    let file_name = match public_url.strip_prefix("/files/") {
+        Some(name) if !name.is_empty() && !name.contains('/') && !name.contains("..") => name,
+        _ => return Ok(()),
+    };
+
+    match tokio::fs::remove_file(format!("./uploads/{}", file_name)).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(Error::delete_file(e)),
+    }
+}